@@ -1,3 +1,25 @@
+use crate::iter_ext::count_ascending;
+use std::collections::VecDeque;
+
+/// The running totals of `data`, starting from 0, so `sums[j] - sums[i]` is the sum of
+/// `data[i..j]`.
+///
+/// # Examples
+///
+/// ```
+/// # use advent_of_code_rust::_2021::_01::prefix_sums;
+///
+/// assert_eq!(prefix_sums(&[1, 2, 3]), vec![0, 1, 3, 6]);
+/// ```
+pub fn prefix_sums(data: &[i32]) -> Vec<i64> {
+    let mut sums = Vec::with_capacity(data.len() + 1);
+    sums.push(0);
+    for &value in data {
+        sums.push(sums[sums.len() - 1] + i64::from(value));
+    }
+    sums
+}
+
 /// Counts the number of increasing pairs in windowed sums of given data.
 ///
 /// # Arguments
@@ -23,24 +45,83 @@
 /// assert_eq!(count, 2);
 /// ```
 pub fn count_of_increasing_pairs_in_windowed_sums(data: &[i32], window_size: usize) -> i32 {
-    let windowed_sums: Vec<i32> = data
-        .windows(window_size)
-        .map(|window| window.iter().sum::<i32>())
-        .collect();
+    // each window's sum is the difference between two prefix sums, however large the window
+    count_ascending(
+        prefix_sums(data)
+            .windows(window_size + 1)
+            .map(|bounds| bounds[window_size] - bounds[0]),
+    ) as i32
+}
 
-    let count_increasing: i32 = windowed_sums
-        .windows(2)
-        .filter(|window_pair| window_pair[0] < window_pair[1])
-        .count() as i32;
+/// Counts the number of increasing pairs in windowed sums, including a final partial window.
+///
+/// The windows slide one element at a time like [`count_of_increasing_pairs_in_windowed_sums`].
+/// After the last full window, the window slides once more off the end of the data, leaving a
+/// shorter window of the last `window_size - 1` elements. A window size of 1 has no partial
+/// window, and neither does data too short to fill one full window. Since a partial window sums
+/// fewer elements, it will usually compare as a decrease unless the tail values are large.
+///
+/// # Arguments
+///
+/// * `data` - A slice of integers.
+/// * `window_size` - The size of the window used to calculate sums.
+///
+/// # Examples
+///
+/// ```
+/// # use advent_of_code_rust::_2021::_01::count_increasing_with_partial;
+///
+/// let data = vec![5, -1, -1, 10];
+/// let count = count_increasing_with_partial(&data, 3);
+/// // 5 - 1 - 1 = 3
+/// // -1 - 1 + 10 = 8
+/// // -1 + 10 = 9 (partial)
+///
+/// assert_eq!(count, 2);
+/// ```
+pub fn count_increasing_with_partial(data: &[i32], window_size: usize) -> usize {
+    let sums = prefix_sums(data);
+    let full_windows = sums
+        .windows(window_size + 1)
+        .map(|bounds| bounds[window_size] - bounds[0]);
+    let partial_window = (window_size > 1 && data.len() >= window_size)
+        .then(|| sums[data.len()] - sums[data.len() + 1 - window_size]);
+
+    count_ascending(full_windows.chain(partial_window))
+}
+
+/// Solves both parts in a single pass without collecting the readings.
+///
+/// Neighboring windows of three share their middle two readings, so a window sum increases
+/// exactly when the incoming reading is larger than the one leaving. Only the last three readings
+/// are kept.
+///
+/// # Returns
+///
+/// The number of increases for window sizes of 1 and 3
+pub fn solve_day1_streaming(lines: impl Iterator<Item = String>) -> (usize, usize) {
+    let mut previous = VecDeque::with_capacity(3);
+    let mut single_increases = 0;
+    let mut window_increases = 0;
 
-    count_increasing
+    for depth in lines.filter_map(|line| line.trim().parse::<i32>().ok()) {
+        if previous.back().is_some_and(|&last| depth > last) {
+            single_increases += 1;
+        }
+        if previous.len() == 3 && previous.pop_front().is_some_and(|leaving| depth > leaving) {
+            window_increases += 1;
+        }
+        previous.push_back(depth);
+    }
+
+    (single_increases, window_increases)
 }
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input_parsing::to_vec;
     use crate::input_parsing::Input::{Path, Raw};
     use crate::input_parsing::Separator::Newline;
+    use crate::input_parsing::{input_path, to_lines, to_vec};
 
     #[test]
     fn test_1_sample() {
@@ -65,7 +146,8 @@ mod tests {
 
     #[test]
     fn test_1() {
-        let numbers = to_vec(Path("input/2021/01.txt"), Newline);
+        let path = input_path(2021, 1);
+        let numbers = to_vec(Path(path.to_str().unwrap()), Newline);
 
         let count = count_of_increasing_pairs_in_windowed_sums(&numbers, 1);
 
@@ -95,10 +177,89 @@ mod tests {
 
     #[test]
     fn test_2() {
-        let numbers: Vec<i32> = to_vec(Path("input/2021/01.txt"), Newline);
+        let path = input_path(2021, 1);
+        let numbers: Vec<i32> = to_vec(Path(path.to_str().unwrap()), Newline);
 
         let count = count_of_increasing_pairs_in_windowed_sums(&numbers, 3);
 
         assert_eq!(count, 1627);
     }
+
+    #[test]
+    fn test_count_increasing_with_partial() {
+        // the partial window 260 + 263 is smaller than the last full window
+        let input = "
+        199
+        200
+        208
+        210
+        200
+        207
+        240
+        269
+        260
+        263
+        ";
+        let numbers = to_vec(Raw(input), Newline);
+
+        assert_eq!(count_increasing_with_partial(&numbers, 3), 5);
+        assert_eq!(count_increasing_with_partial(&[3, -5, 4], 2), 2);
+        // the data divides evenly into windows, but the partial window is still added
+        assert_eq!(count_increasing_with_partial(&[0, 0, 0, -1, 2, 3], 3), 3);
+        assert_eq!(count_increasing_with_partial(&[1, 2, 3], 1), 2);
+        assert_eq!(count_increasing_with_partial(&[1, 2], 3), 0);
+    }
+
+    #[test]
+    fn test_solve_day1_streaming_sample() {
+        let input = to_lines(Raw("
+        199
+        200
+        208
+        210
+        200
+        207
+        240
+        269
+        260
+        263
+        "));
+
+        assert_eq!(solve_day1_streaming(input), (7, 5));
+    }
+
+    #[test]
+    fn test_solve_day1_streaming() {
+        let path = input_path(2021, 1);
+        let input = to_lines(Path(path.to_str().unwrap()));
+
+        assert_eq!(solve_day1_streaming(input), (1583, 1627));
+    }
+
+    /// The original implementation, which sums every window from scratch
+    fn count_by_summing_each_window(data: &[i32], window_size: usize) -> i32 {
+        let windowed_sums: Vec<i32> = data
+            .windows(window_size)
+            .map(|window| window.iter().sum::<i32>())
+            .collect();
+
+        windowed_sums
+            .windows(2)
+            .filter(|window_pair| window_pair[0] < window_pair[1])
+            .count() as i32
+    }
+
+    #[test]
+    fn test_prefix_sums_match_summing_each_window() {
+        let data = (0..200)
+            .map(|i: i32| (i * 7_919) % 211 - 100)
+            .collect::<Vec<_>>();
+
+        for window_size in [1, 2, 3, 10, 199, 200, 201] {
+            assert_eq!(
+                count_of_increasing_pairs_in_windowed_sums(&data, window_size),
+                count_by_summing_each_window(&data, window_size)
+            );
+        }
+    }
 }
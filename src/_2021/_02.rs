@@ -1,4 +1,11 @@
-#[derive(Debug)]
+use crate::_2021::error::Aoc2021Error;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// The `forward`/`down`/`up` table, built once and shared by [`Command`]'s parsers.
+static DEFAULT_PARSER: LazyLock<CommandParser> = LazyLock::new(CommandParser::default);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Command {
     Forward(i32),
     Down(i32),
@@ -7,26 +14,70 @@ pub enum Command {
 
 impl Command {
     pub fn parse(line: &str) -> Option<Self> {
-        let mut line = line.split_whitespace();
-        let direction = line.next()?;
-        let amount = line.next()?;
-        let amount = amount.parse::<i32>().ok()?;
-        match direction {
-            "forward" => Some(Self::Forward(amount)),
-            "down" => Some(Self::Down(amount)),
-            "up" => Some(Self::Up(amount)),
-            _ => None,
-        }
+        Self::try_parse(line).ok()
+    }
+
+    pub fn try_parse(line: &str) -> Result<Self, Aoc2021Error> {
+        DEFAULT_PARSER.try_parse(line)
     }
 
     pub fn parse_batch(lines: impl Iterator<Item = String>) -> Vec<Self> {
         lines
             .into_iter()
-            .filter_map(|line| Self::parse(&line))
+            .filter_map(|line| DEFAULT_PARSER.parse(&line))
             .collect()
     }
 }
 
+/// Parses commands using a table of direction words, so abbreviations or translations can be
+/// registered alongside the defaults of `forward`, `down`, and `up`.
+///
+/// ```
+/// # use advent_of_code_rust::_2021::_02::{Command, CommandParser};
+/// let parser = CommandParser::default().with_alias("f", Command::Forward);
+/// assert_eq!(parser.parse("f 5"), Some(Command::Forward(5)));
+/// ```
+pub struct CommandParser {
+    aliases: HashMap<String, fn(i32) -> Command>,
+}
+
+impl Default for CommandParser {
+    fn default() -> Self {
+        CommandParser {
+            aliases: HashMap::new(),
+        }
+        .with_alias("forward", Command::Forward)
+        .with_alias("down", Command::Down)
+        .with_alias("up", Command::Up)
+    }
+}
+
+impl CommandParser {
+    #[must_use]
+    pub fn with_alias(mut self, alias: &str, command: fn(i32) -> Command) -> Self {
+        self.aliases.insert(alias.to_owned(), command);
+        self
+    }
+
+    pub fn parse(&self, line: &str) -> Option<Command> {
+        self.try_parse(line).ok()
+    }
+
+    pub fn try_parse(&self, line: &str) -> Result<Command, Aoc2021Error> {
+        let mut parts = line.split_whitespace();
+        let direction = parts.next().ok_or(Aoc2021Error::EmptyInput)?;
+        let amount = parts
+            .next()
+            .ok_or_else(|| Aoc2021Error::InvalidCommand(line.trim().to_owned()))?;
+        let amount = amount.parse::<i32>()?;
+        let command = self
+            .aliases
+            .get(direction)
+            .ok_or_else(|| Aoc2021Error::InvalidCommand(direction.to_owned()))?;
+        Ok(command(amount))
+    }
+}
+
 /// Calculates the submarine's distance from origin based on a series of commands.
 ///
 /// # Arguments
@@ -107,8 +158,8 @@ pub fn calculate_aim_and_distance(commands: Vec<Command>) -> i32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input_parsing::to_lines;
     use crate::input_parsing::Input::{Path, Raw};
+    use crate::input_parsing::{input_path, to_lines};
 
     #[test]
     fn test_1_sample() {
@@ -129,7 +180,8 @@ mod tests {
 
     #[test]
     fn test_1() {
-        let input = to_lines(Path("input/2021/02.txt"));
+        let path = input_path(2021, 2);
+        let input = to_lines(Path(path.to_str().unwrap()));
         let commands = Command::parse_batch(input);
 
         let result = calculate_distance(commands);
@@ -156,11 +208,24 @@ mod tests {
 
     #[test]
     fn test_2() {
-        let input = to_lines(Path("input/2021/02.txt"));
+        let path = input_path(2021, 2);
+        let input = to_lines(Path(path.to_str().unwrap()));
         let commands = Command::parse_batch(input);
 
         let result = calculate_aim_and_distance(commands);
 
         assert_eq!(result, 1_842_742_223);
     }
+
+    #[test]
+    fn test_command_parser_aliases() {
+        let parser = CommandParser::default()
+            .with_alias("f", Command::Forward)
+            .with_alias("d", Command::Down);
+
+        assert_eq!(parser.parse("f 5"), Some(Command::Forward(5)));
+        assert_eq!(parser.parse("d 2"), Some(Command::Down(2)));
+        assert_eq!(parser.parse("up 3"), Some(Command::Up(3)));
+        assert_eq!(Command::parse("f 5"), None);
+    }
 }
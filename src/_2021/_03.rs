@@ -1,32 +1,34 @@
-use std::collections::HashMap;
+use crate::iter_ext::most_common_per_position_by;
+use crate::_2021::error::Aoc2021Error;
+use std::cmp::Ordering;
 
-pub fn find_all_most_common_bits(binary_report: &Vec<String>) -> String {
-    let mut freq_of_ones = HashMap::new();
-
-    for s in binary_report {
-        for (i, c) in s.char_indices() {
-            if let '1' = c {
-                let count = freq_of_ones.entry(i).or_insert(0);
-                *count += 1;
-            }
-        }
+/// The width shared by every row of the report, or an error naming the first row that differs.
+pub fn report_width(report: &[String]) -> Result<usize, String> {
+    let width = report.first().ok_or("report is empty")?.len();
+    match report
+        .iter()
+        .enumerate()
+        .find(|(_, row)| row.len() != width)
+    {
+        Some((i, row)) => Err(format!(
+            "row {i} ({row}) has width {}, expected {width}",
+            row.len()
+        )),
+        None => Ok(width),
     }
-    let mut ret = String::new();
+}
 
-    for i in 0..freq_of_ones.len() {
-        match freq_of_ones.get(&i) {
-            Some(i) => {
-                if *i > (binary_report.len() / 2) {
-                    ret.push('1');
-                } else {
-                    ret.push('0');
-                }
-            }
-            _ => panic!("index {i} wasn't found in freq map"),
-        }
-    }
+/// The most common bit in each position, with ties going to `0`.
+pub fn find_all_most_common_bits(binary_report: &[String]) -> String {
+    let rows = binary_report
+        .iter()
+        .map(|s| s.chars().collect())
+        .collect::<Vec<Vec<char>>>();
 
-    ret
+    // '0' sorts before '1', so preferring the smaller bit breaks ties towards '0'
+    most_common_per_position_by(&rows, |a, b| b.cmp(a))
+        .into_iter()
+        .collect()
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -35,43 +37,132 @@ pub enum BitCriteria {
     CO2,
 }
 
-pub fn find_component_rating(mut binary_report: Vec<String>, bit_criteria: BitCriteria) -> String {
-    let mut freq0 = 0;
-    let mut freq1 = 0;
-    let mut position = 0;
+/// Filters the report down to a single rating, one bit position at a time.
+///
+/// Rows may have different lengths: a row too short to reach a position counts as a `0` there.
+/// A position where every survivor has the same bit is skipped rather than filtering out every
+/// row, and once every position is used up the first remaining row is the rating.
+pub fn find_component_rating(binary_report: Vec<String>, bit_criteria: BitCriteria) -> String {
+    component_rating_trace(binary_report, bit_criteria).0
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bit {
+    Zero,
+    One,
+}
+
+impl Bit {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Zero => b'0',
+            Self::One => b'1',
+        }
+    }
+}
+
+/// Same as [`find_component_rating`], but with the criteria spelled out: keep the most or least
+/// common bit, and keep `tie_bit` when both are equally common. Oxygen is `(true, Bit::One)` and
+/// CO2 is `(false, Bit::Zero)`.
+pub fn find_component_rating_with(
+    binary_report: Vec<String>,
+    keep_most_common: bool,
+    tie_bit: Bit,
+) -> String {
+    rating_trace_with(binary_report, keep_most_common, tie_bit).0
+}
 
-    while binary_report.len() != 1 {
-        for s in &binary_report {
-            match s.chars().nth(position) {
-                Some('0') => freq0 += 1,
-                Some('1') => freq1 += 1,
-                Some(e) => panic!("unhandled char {e}"),
-                None => panic!("no char at pos {position}"),
+/// Same as [`find_component_rating`], but also returns each filtering step as
+/// `(position, bit_kept, survivors_remaining)`.
+pub fn component_rating_trace(
+    binary_report: Vec<String>,
+    bit_criteria: BitCriteria,
+) -> (String, Vec<(usize, char, usize)>) {
+    match bit_criteria {
+        BitCriteria::Oxygen => rating_trace_with(binary_report, true, Bit::One),
+        BitCriteria::CO2 => rating_trace_with(binary_report, false, Bit::Zero),
+    }
+}
+
+fn rating_trace_with(
+    mut binary_report: Vec<String>,
+    keep_most_common: bool,
+    tie_bit: Bit,
+) -> (String, Vec<(usize, char, usize)>) {
+    // Count the ones in each column once up front, then subtract each row as it is filtered out,
+    // rather than rescanning every survivor at every position
+    let width = binary_report
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or_default();
+    let mut freq_of_ones = vec![0; width];
+    for s in &binary_report {
+        for (i, c) in s.char_indices() {
+            match c {
+                '0' => (),
+                '1' => freq_of_ones[i] += 1,
+                e => panic!("unhandled char {e}"),
             }
         }
-        let bit_to_keep = match bit_criteria {
-            BitCriteria::Oxygen => {
-                if freq1 >= freq0 {
-                    '1'
-                } else {
-                    '0'
-                }
+    }
+    let mut trace = Vec::new();
+    let mut position = 0;
+
+    while binary_report.len() > 1 && position < width {
+        let freq1 = freq_of_ones[position];
+        let freq0 = binary_report.len() - freq1;
+        if freq1 == 0 || freq0 == 0 {
+            let shared_bit = if freq1 == 0 { '0' } else { '1' };
+            trace.push((position, shared_bit, binary_report.len()));
+            position += 1;
+            continue;
+        }
+        let bit_to_keep = match (freq1.cmp(&freq0), keep_most_common) {
+            (Ordering::Equal, _) => tie_bit.to_byte(),
+            (Ordering::Greater, true) | (Ordering::Less, false) => b'1',
+            (Ordering::Less, true) | (Ordering::Greater, false) => b'0',
+        };
+        binary_report.retain(|s| {
+            let bit = s.as_bytes().get(position).copied().unwrap_or(b'0');
+            if bit == bit_to_keep {
+                return true;
             }
-            BitCriteria::CO2 => {
-                if freq0 > freq1 {
-                    '1'
-                } else {
-                    '0'
+            for (i, c) in s.char_indices() {
+                if c == '1' {
+                    freq_of_ones[i] -= 1;
                 }
             }
-        };
-        binary_report.retain(|s| s.chars().nth(position).eq(&Some(bit_to_keep)));
+            false
+        });
+        trace.push((position, char::from(bit_to_keep), binary_report.len()));
         position += 1;
-        freq0 = 0;
-        freq1 = 0;
     }
 
-    binary_report.pop().unwrap()
+    (binary_report.swap_remove(0), trace)
+}
+
+/// Every rate and rating from a diagnostic report, enough to answer both parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticReport {
+    pub gamma: u32,
+    pub epsilon: u32,
+    pub oxygen: u32,
+    pub co2: u32,
+}
+
+pub fn analyze(report: Vec<String>) -> DiagnosticReport {
+    let gamma_rate = find_all_most_common_bits(&report);
+    let epsilon_rate = flip_binary_str_bits(&gamma_rate);
+    let oxygen_generator_rating = find_component_rating(report.clone(), BitCriteria::Oxygen);
+    let co2_scrubber_rating = find_component_rating(report, BitCriteria::CO2);
+
+    DiagnosticReport {
+        gamma: binary_str_to_decimal(&gamma_rate) as u32,
+        epsilon: binary_str_to_decimal(&epsilon_rate) as u32,
+        oxygen: binary_str_to_decimal(&oxygen_generator_rating) as u32,
+        co2: binary_str_to_decimal(&co2_scrubber_rating) as u32,
+    }
 }
 
 pub fn flip_binary_str_bits(binary: &str) -> String {
@@ -86,7 +177,21 @@ pub fn flip_binary_str_bits(binary: &str) -> String {
 }
 
 pub fn binary_str_to_decimal(binary: &str) -> i32 {
-    i32::from_str_radix(binary, 2).expect("Failed to convert binary string to decimal")
+    try_binary_str_to_decimal(binary).expect("Failed to convert binary string to decimal")
+}
+
+pub fn try_binary_str_to_decimal(binary: &str) -> Result<i32, Aoc2021Error> {
+    if binary.is_empty() {
+        return Err(Aoc2021Error::EmptyInput);
+    }
+    if let Some(bit) = binary.chars().find(|c| !matches!(c, '0' | '1')) {
+        return Err(Aoc2021Error::InvalidBit(bit));
+    }
+    Ok(i32::from_str_radix(binary, 2)?)
+}
+
+pub fn decimal_to_binary_str(value: u32, width: usize) -> String {
+    format!("{value:0width$b}")
 }
 
 #[cfg(test)]
@@ -160,6 +265,17 @@ mod tests {
         assert_eq!(life_support_rating, 230);
     }
 
+    #[test]
+    fn test_find_all_most_common_bits_ties() {
+        let report = vec!["10".to_owned(), "01".to_owned()];
+
+        assert_eq!(find_all_most_common_bits(&report), "00");
+        assert_eq!(
+            find_all_most_common_bits(&["01".to_owned(), "10".to_owned()]),
+            "00"
+        );
+    }
+
     #[test]
     fn test_2() {
         let input: Vec<String> = to_lines(Path("input/2021/03.txt")).collect();
@@ -171,4 +287,185 @@ mod tests {
 
         assert_eq!(life_support_rating, 4_550_283);
     }
+
+    #[test]
+    fn test_analyze_sample() {
+        let input: Vec<String> = to_lines(Raw("
+        00100
+        11110
+        10110
+        10111
+        10101
+        01111
+        00111
+        11100
+        10000
+        11001
+        00010
+        01010
+        "))
+        .collect();
+
+        let report = analyze(input);
+
+        assert_eq!(
+            report,
+            DiagnosticReport {
+                gamma: 22,
+                epsilon: 9,
+                oxygen: 23,
+                co2: 10,
+            }
+        );
+        assert_eq!(report.gamma * report.epsilon, 198);
+        assert_eq!(report.oxygen * report.co2, 230);
+    }
+
+    #[test]
+    fn test_component_rating_trace_sample() {
+        let input: Vec<String> = to_lines(Raw("
+        00100
+        11110
+        10110
+        10111
+        10101
+        01111
+        00111
+        11100
+        10000
+        11001
+        00010
+        01010
+        "))
+        .collect();
+
+        let (rating, trace) = component_rating_trace(input, BitCriteria::Oxygen);
+
+        assert_eq!(rating, "10111");
+        assert_eq!(trace.len(), 5);
+        assert_eq!(
+            trace,
+            vec![
+                (0, '1', 7),
+                (1, '0', 4),
+                (2, '1', 3),
+                (3, '1', 2),
+                (4, '1', 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_component_rating_with_sample() {
+        let input: Vec<String> = to_lines(Raw("
+        00100
+        11110
+        10110
+        10111
+        10101
+        01111
+        00111
+        11100
+        10000
+        11001
+        00010
+        01010
+        "))
+        .collect();
+
+        let oxygen = find_component_rating_with(input.clone(), true, Bit::One);
+        let co2 = find_component_rating_with(input.clone(), false, Bit::Zero);
+
+        assert_eq!(binary_str_to_decimal(&oxygen), 23);
+        assert_eq!(binary_str_to_decimal(&co2), 10);
+        assert_eq!(
+            oxygen,
+            find_component_rating(input.clone(), BitCriteria::Oxygen)
+        );
+        assert_eq!(co2, find_component_rating(input, BitCriteria::CO2));
+
+        let tied = vec!["10".to_owned(), "01".to_owned()];
+        assert_eq!(
+            find_component_rating_with(tied.clone(), true, Bit::Zero),
+            "01"
+        );
+        assert_eq!(find_component_rating_with(tied, false, Bit::One), "10");
+    }
+
+    #[test]
+    fn test_decimal_to_binary_str() {
+        assert_eq!(
+            decimal_to_binary_str(binary_str_to_decimal("00100") as u32, 5),
+            "00100"
+        );
+        assert_eq!(decimal_to_binary_str(22, 5), "10110");
+    }
+
+    /// The original implementation, which rescans every survivor at each position
+    fn find_component_rating_by_rescanning(
+        mut binary_report: Vec<String>,
+        bit_criteria: BitCriteria,
+    ) -> String {
+        let mut position = 0;
+        while binary_report.len() != 1 {
+            let freq1 = binary_report
+                .iter()
+                .filter(|s| s.chars().nth(position) == Some('1'))
+                .count();
+            let freq0 = binary_report.len() - freq1;
+            let bit_to_keep = match bit_criteria {
+                BitCriteria::Oxygen if freq1 >= freq0 => '1',
+                BitCriteria::CO2 if freq0 > freq1 => '1',
+                _ => '0',
+            };
+            binary_report.retain(|s| s.chars().nth(position).eq(&Some(bit_to_keep)));
+            position += 1;
+        }
+        binary_report.pop().unwrap()
+    }
+
+    #[test]
+    fn test_find_component_rating_matches_rescanning() {
+        // 500 distinct 12 bit rows; an odd multiplier is a permutation mod 4096
+        let input: Vec<String> = (0..500_u32)
+            .map(|i| decimal_to_binary_str((i * 1_237) % 4096, 12))
+            .collect();
+
+        for bit_criteria in [BitCriteria::Oxygen, BitCriteria::CO2] {
+            assert_eq!(
+                find_component_rating(input.clone(), bit_criteria),
+                find_component_rating_by_rescanning(input.clone(), bit_criteria)
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_width() {
+        let report: Vec<String> = to_lines(Raw("
+        00100
+        11110
+        1011
+        10111
+        "))
+        .collect();
+
+        assert_eq!(
+            report_width(&report),
+            Err("row 2 (1011) has width 4, expected 5".to_string())
+        );
+        assert_eq!(report_width(&report[..2]), Ok(5));
+        assert_eq!(report_width(&[]), Err("report is empty".to_string()));
+    }
+
+    #[test]
+    fn test_find_component_rating_uneven_rows() {
+        let report = vec!["1011".to_owned(), "10".to_owned(), "100".to_owned()];
+
+        // "10" and "100" tie on every position once "1011" is gone, so the first one is kept
+        assert_eq!(
+            find_component_rating(report.clone(), BitCriteria::Oxygen),
+            "10"
+        );
+        assert_eq!(find_component_rating(report, BitCriteria::CO2), "1011");
+    }
 }
@@ -1,7 +1,8 @@
+use crate::_2021::error::Aoc2021Error;
 use itertools::Itertools;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BingoBoard([[BingoCell; 5]; 5]);
 
 #[derive(Debug, Copy, Clone)]
@@ -9,6 +10,20 @@ pub enum BingoCell {
     Marked(i32),
     Unmarked(i32),
 }
+/// A completed line on a board, by its index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WinKind {
+    Row(usize),
+    Column(usize),
+}
+
+impl BingoCell {
+    pub fn value(self) -> i32 {
+        match self {
+            BingoCell::Marked(value) | BingoCell::Unmarked(value) => value,
+        }
+    }
+}
 impl BingoBoard {
     // Extracting cell parsing logic to a separate function
     fn parse_cell(number_str: &str) -> Option<BingoCell> {
@@ -35,16 +50,20 @@ impl BingoBoard {
             .collect()
     }
 
-    pub fn calculate_score(&self, last_call: i32) -> i32 {
-        let mut score = 0;
+    pub fn unmarked_sum(&self) -> i32 {
+        let mut sum = 0;
         for row in &self.0 {
             for cell in row {
                 if let BingoCell::Unmarked(value) = cell {
-                    score += value;
+                    sum += value;
                 }
             }
         }
-        score * last_call
+        sum
+    }
+
+    pub fn calculate_score(&self, last_call: i32) -> i32 {
+        self.unmarked_sum() * last_call
     }
 
     pub fn mark(&mut self, number: i32) {
@@ -59,6 +78,56 @@ impl BingoBoard {
         }
     }
 
+    /// Marks every number in `numbers` with a single pass over the board.
+    pub fn mark_all(&mut self, numbers: &[i32]) {
+        let numbers = numbers.iter().collect::<HashSet<_>>();
+        for row in &mut self.0 {
+            for cell in row {
+                if let BingoCell::Unmarked(value) = cell {
+                    if numbers.contains(value) {
+                        *cell = BingoCell::Marked(*value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Maps each value on the board to its flat index, `row * 5 + column`, which is the bit a
+    /// mask-based board would use for that cell.
+    pub fn board_to_value_index(&self) -> HashMap<i32, usize> {
+        self.0
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, cell)| (cell.value(), i))
+            .collect()
+    }
+
+    /// Whether the board reads the same with its rows and columns swapped. Only the values are
+    /// compared, not which cells are marked.
+    pub fn is_symmetric(&self) -> bool {
+        (0..5).all(|row| (0..row).all(|col| self.0[row][col].value() == self.0[col][row].value()))
+    }
+
+    // Every row followed by every column
+    fn lines(&self) -> impl Iterator<Item = [BingoCell; 5]> + '_ {
+        let rows = self.0.iter().copied();
+        let cols = (0..5).map(|col| self.0.map(|row| row[col]));
+        rows.chain(cols)
+    }
+
+    /// Every completed row, then every completed column.
+    pub fn all_winning_lines(&self) -> Vec<WinKind> {
+        let is_marked = |cell: BingoCell| matches!(cell, BingoCell::Marked(_));
+        let rows = (0..5)
+            .filter(|&row| self.0[row].iter().all(|&cell| is_marked(cell)))
+            .map(WinKind::Row);
+        let cols = (0..5)
+            .filter(|&col| self.0.iter().all(|row| is_marked(row[col])))
+            .map(WinKind::Column);
+        rows.chain(cols).collect()
+    }
+
     pub fn is_winner(&self) -> bool {
         for row in &self.0 {
             if row.iter().all(|&cell| matches!(cell, BingoCell::Marked(_))) {
@@ -90,33 +159,237 @@ pub fn parse_calls_and_bingo_boards(
     (calls, boards)
 }
 
-pub fn play_bingo(calls: Vec<i32>, mut boards: Vec<BingoBoard>) -> Vec<i32> {
-    let mut winning_scores = Vec::new();
-    let mut past_winners = HashSet::new();
+/// Like [`parse_calls_and_bingo_boards`], but fails on a call or cell that isn't a number, a
+/// board row without exactly 5 cells, or a board cut short, instead of skipping or panicking.
+pub fn try_parse_calls_and_bingo_boards(
+    mut lines: impl Iterator<Item = String>,
+) -> Result<(Vec<i32>, Vec<BingoBoard>), Aoc2021Error> {
+    let calls = lines.next().ok_or(Aoc2021Error::EmptyInput)?;
+    let calls = calls
+        .split(',')
+        .map(|s| s.trim().parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let rows = lines
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect_vec();
+    for row in &rows {
+        let values = row
+            .split_whitespace()
+            .map(str::parse::<i32>)
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() != 5 {
+            return Err(Aoc2021Error::InvalidRow(row.clone()));
+        }
+    }
+    if rows.len() % 5 != 0 {
+        return Err(Aoc2021Error::IncompleteBoard);
+    }
+    Ok((calls, BingoBoard::parse_batch(rows.into_iter())))
+}
+
+pub fn play_bingo(calls: Vec<i32>, boards: Vec<BingoBoard>) -> Vec<i32> {
+    play(calls, boards)
+        .flat_map(|(_, call, winners)| {
+            winners
+                .into_iter()
+                .map(move |(_, board)| board.calculate_score(call))
+        })
+        .collect()
+}
+
+/// The sum of every winning score, in the order the boards win.
+pub fn total_winning_score(calls: Vec<i32>, boards: Vec<BingoBoard>) -> i32 {
+    play_bingo(calls, boards).into_iter().sum()
+}
+
+/// The indices of the boards that would have won by the time every remaining call is drawn.
+pub fn boards_that_win_eventually(remaining_calls: &[i32], boards: &[BingoBoard]) -> Vec<usize> {
+    boards
+        .iter()
+        .enumerate()
+        .filter(|(_, board)| {
+            let mut board = (*board).clone();
+            board.mark_all(remaining_calls);
+            board.is_winner()
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// The fewest calls, in order, that would make `board` win: the unmarked values of whichever
+/// line is closest to complete.
+pub fn fastest_winning_order(board: &BingoBoard) -> Vec<i32> {
+    board
+        .lines()
+        .map(|line| {
+            line.iter()
+                .filter_map(|cell| match cell {
+                    BingoCell::Unmarked(value) => Some(*value),
+                    BingoCell::Marked(_) => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .min_by_key(Vec::len)
+        .unwrap_or_default()
+}
 
-    for call in calls {
-        for (i, board) in boards.iter_mut().enumerate() {
+pub fn total_remaining_unmarked(boards: &[BingoBoard]) -> i64 {
+    boards
+        .iter()
+        .map(|board| i64::from(board.unmarked_sum()))
+        .sum()
+}
+
+/// Marks calls on `board` until it wins, returning the winning board and the call that won it.
+///
+/// # Panics
+///
+/// If the board never wins with the given calls
+pub fn replay_until_win(calls: &[i32], board: BingoBoard) -> (BingoBoard, i32) {
+    try_replay_until_win(calls, board).expect("board never wins")
+}
+
+fn try_replay_until_win(calls: &[i32], board: BingoBoard) -> Option<(BingoBoard, i32)> {
+    play(calls.iter().copied(), vec![board])
+        .find_map(|(_, call, winners)| winners.into_iter().next().map(|(_, board)| (board, call)))
+}
+
+/// How many calls each board needs before it wins, or `None` if it never does.
+pub fn turns_to_win(calls: &[i32], boards: &[BingoBoard]) -> Vec<Option<usize>> {
+    let mut turns = vec![None; boards.len()];
+    for (turn, _, winners) in play(calls.iter().copied(), boards.to_vec()) {
+        for (i, _) in winners {
+            turns[i] = Some(turn);
+        }
+    }
+    turns
+}
+
+/// The score `board` would get playing on its own, or `None` if it never wins.
+pub fn play_bingo_single(calls: &[i32], board: BingoBoard) -> Option<i32> {
+    try_replay_until_win(calls, board).map(|(board, call)| board.calculate_score(call))
+}
+
+/// The calls, in order, whose numbers don't appear anywhere on `board`.
+pub fn unused_calls(calls: &[i32], board: &BingoBoard) -> Vec<i32> {
+    let values = board.board_to_value_index();
+    calls
+        .iter()
+        .copied()
+        .filter(|call| !values.contains_key(call))
+        .collect()
+}
+
+/// Whether any board would have a complete row or column once every call is drawn.
+pub fn any_board_can_win(calls: &[i32], boards: &[BingoBoard]) -> bool {
+    boards.iter().any(|board| {
+        let mut board = board.clone();
+        board.mark_all(calls);
+        board.is_winner()
+    })
+}
+
+/// The number of calls drawn up to and including the one that produces the first winner.
+///
+/// The boards are left untouched; the game is played on clones.
+pub fn calls_to_first_win(calls: &[i32], boards: &[BingoBoard]) -> Option<usize> {
+    play(calls.iter().copied(), boards.to_vec())
+        .find_map(|(turn, _, winners)| (!winners.is_empty()).then_some(turn))
+}
+
+/// Solves both parts from the raw input, alongside how many calls it took to get each winner.
+///
+/// # Returns
+///
+/// `(calls, score)` for the first board to win, then the same for the last board to win
+pub fn solve_day4(lines: impl Iterator<Item = String>) -> ((usize, i32), (usize, i32)) {
+    let (calls, boards) = parse_calls_and_bingo_boards(lines);
+    let wins = play(calls, boards)
+        .flat_map(|(turn, call, winners)| {
+            winners
+                .into_iter()
+                .map(move |(_, board)| (turn, board.calculate_score(call)))
+        })
+        .collect_vec();
+
+    (
+        *wins.first().expect("a board should win"),
+        *wins.last().expect("a board should win"),
+    )
+}
+
+/// The game as a timeline: each call alongside the original indices of the boards that won on it.
+pub fn bingo_events(calls: &[i32], boards: Vec<BingoBoard>) -> Vec<(i32, Vec<usize>)> {
+    play(calls.iter().copied(), boards)
+        .map(|(_, call, winners)| (call, winners.into_iter().map(|(i, _)| i).collect()))
+        .collect()
+}
+
+// The one game loop everything else is built on. Lazily yields, for each call, how many calls
+// have been drawn so far, the call itself, and the boards that won on it alongside their
+// original index. Winners are taken out of play, so each board wins at most once.
+fn play(
+    calls: impl IntoIterator<Item = i32>,
+    boards: Vec<BingoBoard>,
+) -> impl Iterator<Item = (usize, i32, Vec<(usize, BingoBoard)>)> {
+    let mut boards = boards.into_iter().enumerate().collect_vec();
+    calls.into_iter().enumerate().map(move |(i, call)| {
+        for (_, board) in &mut boards {
             board.mark(call);
-            if board.is_winner() {
-                winning_scores.push(board.calculate_score(call));
-                past_winners.insert(i);
-            }
         }
-        let mut i: usize = 0;
-        boards.retain(|_| {
-            let keep = !past_winners.contains(&i);
-            i += 1;
-            keep
-        });
-        past_winners.clear();
+        let (winners, remaining) = boards
+            .drain(..)
+            .partition::<Vec<_>, _>(|(_, board)| board.is_winner());
+        boards = remaining;
+        (i + 1, call, winners)
+    })
+}
+
+/// Plays bingo until every board has won, recording after each call which boards
+/// have won so far. Boards keep their original order.
+pub fn play_bingo_history(calls: Vec<i32>, boards: Vec<BingoBoard>) -> Vec<Vec<bool>> {
+    let mut history = Vec::new();
+    let mut has_won = vec![false; boards.len()];
+
+    for (_, _, winners) in play(calls, boards) {
+        if has_won.iter().all(|&won| won) {
+            break;
+        }
+        for (i, _) in winners {
+            has_won[i] = true;
+        }
+        history.push(has_won.clone());
     }
 
-    winning_scores
+    history
 }
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::input_parsing::{to_lines, Input::*};
+
+    const SAMPLE: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
     #[test]
     fn test_1_sample() {
         let input = to_lines(Raw("
@@ -200,4 +473,325 @@ mod tests {
 
         assert_eq!(*winning_scores.last().unwrap(), 12_738);
     }
+
+    #[test]
+    fn test_play_bingo_history_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        let history = play_bingo_history(calls, boards);
+
+        assert_eq!(history.first().unwrap(), &vec![false, false, false]);
+        // the third board wins on the 12th call (24), the second board is last on the 15th (13)
+        assert_eq!(history[11], vec![false, false, true]);
+        assert_eq!(history.len(), 15);
+        assert_eq!(history.last().unwrap(), &vec![true, true, true]);
+    }
+
+    #[test]
+    fn test_mark_all() {
+        let mut board = BingoBoard::parse(to_lines(Raw("
+            22 13 17 11  0
+             8  2 23  4 24
+            21  9 14 16  7
+             6 10  3 18  5
+             1 12 20 15 19
+            ")))
+        .unwrap();
+
+        board.mark_all(&[8, 2, 23, 4]);
+        assert!(!board.is_winner());
+
+        board.mark_all(&[99, 24]);
+        assert!(board.is_winner());
+        assert_eq!(board.calculate_score(24), (300 - 61) * 24);
+    }
+
+    #[test]
+    fn test_calls_to_first_win_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        let count = calls_to_first_win(&calls, &boards).unwrap();
+        assert_eq!(count, 12);
+
+        let mut winner = boards[2].clone();
+        winner.mark_all(&calls[..count]);
+        assert!(winner.is_winner());
+        assert_eq!(winner.calculate_score(calls[count - 1]), 4_512);
+
+        assert_eq!(calls_to_first_win(&calls[..count - 1], &boards), None);
+    }
+
+    #[test]
+    fn test_board_to_value_index() {
+        let board = BingoBoard::parse(to_lines(Raw("
+            22 13 17 11  0
+             8  2 23  4 24
+            21  9 14 16  7
+             6 10  3 18  5
+             1 12 20 15 19
+            ")))
+        .unwrap();
+
+        let index = board.board_to_value_index();
+
+        assert_eq!(index.len(), 25);
+        assert_eq!(
+            index.values().copied().collect::<HashSet<_>>(),
+            (0..25).collect()
+        );
+        assert_eq!(index[&22], 0);
+        assert_eq!(index[&24], 9);
+        assert_eq!(index[&19], 24);
+    }
+
+    #[test]
+    fn test_any_board_can_win_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        assert!(any_board_can_win(&calls, &boards));
+        // no board has a full line until the 12th call
+        assert!(!any_board_can_win(&calls[..11], &boards));
+        assert!(!any_board_can_win(&[99, 98, 97], &boards));
+    }
+
+    #[test]
+    fn test_fastest_winning_order() {
+        let mut board = BingoBoard::parse(to_lines(Raw("
+            22 13 17 11  0
+             8  2 23  4 24
+            21  9 14 16  7
+             6 10  3 18  5
+             1 12 20 15 19
+            ")))
+        .unwrap();
+
+        assert_eq!(fastest_winning_order(&board), vec![22, 13, 17, 11, 0]);
+
+        board.mark_all(&[9, 10, 12, 5]);
+        let order = fastest_winning_order(&board);
+        assert_eq!(order, vec![13, 2]);
+
+        board.mark_all(&order);
+        assert!(board.is_winner());
+    }
+
+    #[test]
+    fn test_total_remaining_unmarked() {
+        let mut boards = BingoBoard::parse_batch(to_lines(Raw("
+        22 13 17 11  0
+         8  2 23  4 24
+        21  9 14 16  7
+         6 10  3 18  5
+         1 12 20 15 19
+
+         3 15  0  2 22
+         9 18 13 17  5
+        19  8  7 25 23
+        20 11 10 24  4
+        14 21 16 12  6
+        ")));
+        boards[0].mark_all(&[22, 13, 17, 11, 0]);
+        boards[1].mark_all(&[3, 9, 19]);
+
+        assert_eq!(boards[0].unmarked_sum(), 300 - 63);
+        assert_eq!(boards[1].unmarked_sum(), 324 - 31);
+        assert_eq!(total_remaining_unmarked(&boards), 237 + 293);
+    }
+
+    #[test]
+    fn test_replay_until_win_sample() {
+        let input = to_lines(Raw("
+        7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+        14 21 17 24  4
+        10 16 15  9 19
+        18  8 23 26 20
+        22 11 13  6  5
+         2  0 12  3  7
+        "));
+
+        let (calls, mut boards) = parse_calls_and_bingo_boards(input);
+
+        let (board, last_call) = replay_until_win(&calls, boards.remove(0));
+
+        assert_eq!(last_call, 24);
+        assert!(board.is_winner());
+        assert_eq!(board.calculate_score(last_call), 4_512);
+    }
+
+    #[test]
+    fn test_solve_day4_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        assert_eq!(solve_day4(input), ((12, 4_512), (15, 1_924)));
+    }
+
+    #[test]
+    fn test_solve_day4() {
+        let input = to_lines(Path("input/2021/04.txt"));
+
+        let ((_, first_score), (_, last_score)) = solve_day4(input);
+
+        assert_eq!(first_score, 8_136);
+        assert_eq!(last_score, 12_738);
+    }
+
+    #[test]
+    fn test_unused_calls_sample() {
+        let input = to_lines(Raw("
+        7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+        14 21 17 24  4
+        10 16 15  9 19
+        18  8 23 26 20
+        22 11 13  6  5
+         2  0 12  3  7
+        "));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        assert_eq!(unused_calls(&calls, &boards[0]), vec![25, 1]);
+        assert_eq!(unused_calls(&calls[..12], &boards[0]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_bingo_events_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        let events = bingo_events(&calls, boards.clone());
+
+        assert_eq!(events.len(), calls.len());
+        let (call, winners) = events
+            .iter()
+            .find(|(_, winners)| !winners.is_empty())
+            .unwrap();
+        assert_eq!((*call, winners.clone()), (24, vec![2]));
+        let (board, last_call) = replay_until_win(&calls, boards[winners[0]].clone());
+        assert_eq!(board.calculate_score(last_call), 4_512);
+        assert_eq!(events.iter().flat_map(|(_, winners)| winners).count(), 3);
+    }
+
+    #[test]
+    fn test_play_bingo_single_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        assert_eq!(play_bingo_single(&calls, boards[2].clone()), Some(4_512));
+        assert_eq!(play_bingo_single(&calls[..11], boards[2].clone()), None);
+    }
+
+    #[test]
+    fn test_total_winning_score_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        let scores = play_bingo(calls.clone(), boards.clone());
+        assert_eq!(scores, vec![4_512, 2_192, 1_924]);
+        assert_eq!(total_winning_score(calls, boards), 4_512 + 2_192 + 1_924);
+    }
+
+    #[test]
+    fn test_is_symmetric() {
+        let boards = BingoBoard::parse_batch(to_lines(Raw("
+         1  2  3  4  5
+         2  6  7  8  9
+         3  7 10 11 12
+         4  8 11 13 14
+         5  9 12 14 15
+
+        22 13 17 11  0
+         8  2 23  4 24
+        21  9 14 16  7
+         6 10  3 18  5
+         1 12 20 15 19
+        ")));
+
+        let mut symmetric = boards[0].clone();
+        assert!(symmetric.is_symmetric());
+        // marking doesn't change the values
+        symmetric.mark(7);
+        assert!(symmetric.is_symmetric());
+        assert!(!boards[1].is_symmetric());
+    }
+
+    #[test]
+    fn test_boards_that_win_eventually_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, mut boards) = parse_calls_and_bingo_boards(input);
+        // nobody has won after the first 11 calls
+        let (drawn, remaining) = calls.split_at(11);
+        for board in &mut boards {
+            board.mark_all(drawn);
+        }
+
+        assert_eq!(
+            boards_that_win_eventually(&[], &boards),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            boards_that_win_eventually(&remaining[..1], &boards),
+            vec![2]
+        );
+        assert_eq!(
+            boards_that_win_eventually(remaining, &boards),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_all_winning_lines() {
+        let mut boards = BingoBoard::parse_batch(to_lines(Raw("
+        22 13 17 11  0
+         8  2 23  4 24
+        21  9 14 16  7
+         6 10  3 18  5
+         1 12 20 15 19
+        ")));
+        let board = &mut boards[0];
+
+        assert_eq!(board.all_winning_lines(), vec![]);
+        board.mark_all(&[22, 13, 17, 11, 0, 21, 9, 14, 16]);
+        assert_eq!(board.all_winning_lines(), vec![WinKind::Row(0)]);
+        board.mark(7);
+        assert_eq!(
+            board.all_winning_lines(),
+            vec![WinKind::Row(0), WinKind::Row(2)]
+        );
+        board.mark_all(&[8, 6, 1]);
+        assert_eq!(
+            board.all_winning_lines(),
+            vec![WinKind::Row(0), WinKind::Row(2), WinKind::Column(0)]
+        );
+    }
+
+    #[test]
+    fn test_turns_to_win_sample() {
+        let input = to_lines(Raw(SAMPLE));
+
+        let (calls, boards) = parse_calls_and_bingo_boards(input);
+
+        let turns = turns_to_win(&calls, &boards);
+        let fastest = turns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, turn)| turn.map(|turn| (i, turn)))
+            .min_by_key(|&(_, turn)| turn);
+        assert_eq!(fastest, Some((2, 12)));
+        assert_eq!(turns[1], Some(15));
+        assert_eq!(
+            turns_to_win(&calls[..12], &boards),
+            vec![None, None, Some(12)]
+        );
+    }
 }
@@ -1,38 +1,46 @@
-use std::collections::HashMap;
+use crate::grid::parse_segment;
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
 
-#[derive(Hash, Eq, PartialEq, Copy, Clone)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
-impl Point {
-    pub fn parse_line_to_point(point_str: &str) -> Option<Self> {
-        let (x_str, y_str) = point_str.split_once(',')?;
-        let x = x_str.trim().parse::<i32>().ok()?;
-        let y = y_str.trim().parse::<i32>().ok()?;
-        Some(Point { x, y })
-    }
-
-    pub fn parse_line_to_pair(line: &str) -> Option<(Self, Self)> {
-        let (start_str, end_str) = line.split_once("->")?;
-        let start_point = Self::parse_line_to_point(start_str)?;
-        let end_point = Self::parse_line_to_point(end_str)?;
-        Some((start_point, end_point))
-    }
+pub use crate::grid::Point;
 
-    pub fn parse_batch(lines: impl Iterator<Item = String>) -> impl Iterator<Item = (Self, Self)> {
-        lines
-            .into_iter()
-            .filter_map(|line| Self::parse_line_to_pair(&line))
-    }
+/// Parses every `"x,y -> x,y"` line into a segment, skipping lines that don't parse.
+pub fn parse_batch_segments(
+    lines: impl Iterator<Item = String>,
+) -> impl Iterator<Item = (Point, Point)> {
+    lines.filter_map(|line| parse_segment(&line))
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Diagonals {
     Include,
     Exclude,
 }
 
+impl FromStr for Diagonals {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "include" => Ok(Self::Include),
+            "exclude" => Ok(Self::Exclude),
+            _ => Err(format!("unknown diagonals mode {s}")),
+        }
+    }
+}
+
+/// Drops segments that repeat an earlier one, in either direction, keeping the first occurrence.
+pub fn dedup_segments(segments: Vec<(Point, Point)>) -> Vec<(Point, Point)> {
+    let mut seen = HashSet::new();
+    segments
+        .into_iter()
+        .filter(|&(start, end)| !seen.contains(&(end, start)) && seen.insert((start, end)))
+        .collect()
+}
+
+/// Counts how many lines cross each point. Duplicate segments are each counted, so run them
+/// through [`dedup_segments`] first if a repeated line shouldn't add to the overlap.
 pub fn plot_points(
     points: impl Iterator<Item = (Point, Point)>,
     plot_diagonals: Diagonals,
@@ -74,6 +82,71 @@ pub fn count_overlapping_points(grid: HashMap<Point, i32>) -> i32 {
         count
     })
 }
+
+/// The number of cells touched by at least one line, however many lines overlap there.
+pub fn distinct_cells(grid: &HashMap<Point, i32>) -> usize {
+    grid.len()
+}
+
+/// The number of cells crossed by exactly `n` lines.
+pub fn count_points_crossed_exactly(grid: &HashMap<Point, i32>, n: i32) -> usize {
+    grid.values().filter(|&&count| count == n).count()
+}
+
+/// The number of cell edges between a plotted cell and an unplotted one.
+pub fn plotted_perimeter(grid: &HashMap<Point, i32>) -> usize {
+    grid.keys()
+        .flat_map(|point| point.neighbors())
+        .filter(|neighbor| !grid.contains_key(neighbor))
+        .count()
+}
+
+/// Maps each overlap count to the number of cells crossed by exactly that many lines.
+pub fn overlap_histogram(grid: &HashMap<Point, i32>) -> BTreeMap<i32, usize> {
+    grid.values()
+        .fold(BTreeMap::new(), |mut histogram, &count| {
+            *histogram.entry(count).or_insert(0) += 1;
+            histogram
+        })
+}
+
+/// The sum of every cell's count, which equals the total length of all plotted lines.
+pub fn total_overlap(grid: &HashMap<Point, i32>) -> i64 {
+    grid.values().map(|&count| i64::from(count)).sum()
+}
+
+/// Draws the grid the way the puzzle does: `.` for empty cells, otherwise the number of lines.
+pub fn render_vent_grid(grid: &HashMap<Point, i32>) -> String {
+    render_vent_grid_custom(grid, '.', i32::MAX, '#')
+}
+
+/// Draws the grid with `empty` for untouched cells and `hot` for cells crossed by at least
+/// `overlap_threshold` lines. Other cells show their count, or `+` if it is more than 9.
+pub fn render_vent_grid_custom(
+    grid: &HashMap<Point, i32>,
+    empty: char,
+    overlap_threshold: i32,
+    hot: char,
+) -> String {
+    let Some((min_x, max_x)) = grid.keys().map(|p| p.x).minmax().into_option() else {
+        return String::new();
+    };
+    let Some((min_y, max_y)) = grid.keys().map(|p| p.y).minmax().into_option() else {
+        return String::new();
+    };
+
+    (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| match grid.get(&Point { x, y }) {
+                    None => empty,
+                    Some(&count) if count >= overlap_threshold => hot,
+                    Some(&count) => char::from_digit(count as u32, 10).unwrap_or('+'),
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,7 +166,7 @@ mod tests {
         5,5 -> 8,2
         "));
 
-        let grid = plot_points(Point::parse_batch(input), Diagonals::Exclude);
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Exclude);
 
         assert_eq!(count_overlapping_points(grid), 5);
     }
@@ -102,7 +175,7 @@ mod tests {
     fn test_1() {
         let input = to_lines(Path("input/2021/05.txt"));
 
-        let grid = plot_points(Point::parse_batch(input), Diagonals::Exclude);
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Exclude);
 
         assert_eq!(count_overlapping_points(grid), 8_111);
     }
@@ -122,7 +195,7 @@ mod tests {
         5,5 -> 8,2
         "));
 
-        let grid = plot_points(Point::parse_batch(input), Diagonals::Include);
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Include);
 
         assert_eq!(count_overlapping_points(grid), 12);
     }
@@ -131,8 +204,196 @@ mod tests {
     fn test_2() {
         let input = to_lines(Path("input/2021/05.txt"));
 
-        let grid = plot_points(Point::parse_batch(input), Diagonals::Include);
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Include);
 
         assert_eq!(count_overlapping_points(grid), 22_088);
     }
+
+    #[test]
+    fn test_distinct_cells_sample() {
+        let input = to_lines(Raw("
+        0,9 -> 5,9
+        8,0 -> 0,8
+        9,4 -> 3,4
+        2,2 -> 2,1
+        7,0 -> 7,4
+        6,4 -> 2,0
+        0,9 -> 2,9
+        3,4 -> 1,4
+        0,0 -> 8,8
+        5,5 -> 8,2
+        "));
+
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Include);
+
+        assert_eq!(distinct_cells(&grid), 39);
+    }
+
+    #[test]
+    fn test_diagonals_from_str() {
+        assert_eq!("include".parse(), Ok(Diagonals::Include));
+        assert_eq!("EXCLUDE".parse(), Ok(Diagonals::Exclude));
+        assert_eq!(
+            "both".parse::<Diagonals>(),
+            Err("unknown diagonals mode both".to_string())
+        );
+    }
+
+    #[test]
+    fn test_overlap_histogram_sample() {
+        let input = to_lines(Raw("
+        0,9 -> 5,9
+        8,0 -> 0,8
+        9,4 -> 3,4
+        2,2 -> 2,1
+        7,0 -> 7,4
+        6,4 -> 2,0
+        0,9 -> 2,9
+        3,4 -> 1,4
+        0,0 -> 8,8
+        5,5 -> 8,2
+        "));
+
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Include);
+
+        assert_eq!(
+            overlap_histogram(&grid),
+            BTreeMap::from([(1, 27), (2, 10), (3, 2)])
+        );
+    }
+
+    #[test]
+    fn test_total_overlap_sample() {
+        let input = to_lines(Raw("
+        0,9 -> 5,9
+        8,0 -> 0,8
+        9,4 -> 3,4
+        2,2 -> 2,1
+        7,0 -> 7,4
+        6,4 -> 2,0
+        0,9 -> 2,9
+        3,4 -> 1,4
+        0,0 -> 8,8
+        5,5 -> 8,2
+        "));
+        let segments = parse_batch_segments(input).collect::<Vec<_>>();
+        let total_length: i64 = segments
+            .iter()
+            .map(|(start, end)| i64::from((start.x - end.x).abs().max((start.y - end.y).abs()) + 1))
+            .sum();
+
+        let grid = plot_points(segments.into_iter(), Diagonals::Include);
+
+        assert_eq!(total_length, 53);
+        assert_eq!(total_overlap(&grid), total_length);
+    }
+
+    #[test]
+    fn test_render_vent_grid_sample() {
+        let input = to_lines(Raw("
+        0,9 -> 5,9
+        8,0 -> 0,8
+        9,4 -> 3,4
+        2,2 -> 2,1
+        7,0 -> 7,4
+        6,4 -> 2,0
+        0,9 -> 2,9
+        3,4 -> 1,4
+        0,0 -> 8,8
+        5,5 -> 8,2
+        "));
+        let segments = parse_batch_segments(input).collect::<Vec<_>>();
+
+        let grid = plot_points(segments.clone().into_iter(), Diagonals::Exclude);
+        assert_eq!(
+            render_vent_grid(&grid),
+            "\
+.......1..
+..1....1..
+..1....1..
+.......1..
+.112111211
+..........
+..........
+..........
+..........
+222111...."
+        );
+
+        let grid = plot_points(segments.into_iter(), Diagonals::Include);
+        let rendered = render_vent_grid_custom(&grid, ' ', 2, '#');
+        assert_eq!(
+            rendered.chars().filter(|&c| c == '#').count() as i32,
+            count_overlapping_points(grid)
+        );
+    }
+
+    #[test]
+    fn test_dedup_segments() {
+        let segments = parse_batch_segments(to_lines(Raw("
+        0,9 -> 5,9
+        8,0 -> 0,8
+        0,9 -> 5,9
+        5,9 -> 0,9
+        0,8 -> 8,0
+        3,4 -> 1,4
+        ")))
+        .collect_vec();
+
+        let deduped = dedup_segments(segments.clone());
+
+        assert_eq!(deduped, vec![segments[0], segments[1], segments[5]]);
+        assert_eq!(
+            count_overlapping_points(plot_points(segments.into_iter(), Diagonals::Include)),
+            15
+        );
+        assert_eq!(
+            count_overlapping_points(plot_points(deduped.into_iter(), Diagonals::Include)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_count_points_crossed_exactly_sample() {
+        let input = to_lines(Raw("
+        0,9 -> 5,9
+        8,0 -> 0,8
+        9,4 -> 3,4
+        2,2 -> 2,1
+        7,0 -> 7,4
+        6,4 -> 2,0
+        0,9 -> 2,9
+        3,4 -> 1,4
+        0,0 -> 8,8
+        5,5 -> 8,2
+        "));
+
+        let grid = plot_points(parse_batch_segments(input), Diagonals::Include);
+
+        assert_eq!(count_points_crossed_exactly(&grid, 3), 2);
+        assert_eq!(count_points_crossed_exactly(&grid, 2), 10);
+        assert_eq!(count_points_crossed_exactly(&grid, 4), 0);
+    }
+
+    #[test]
+    fn test_plotted_perimeter() {
+        let plot = |points: &[(i32, i32)]| {
+            points
+                .iter()
+                .map(|&(x, y)| (Point { x, y }, 1))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(plotted_perimeter(&HashMap::new()), 0);
+        assert_eq!(plotted_perimeter(&plot(&[(3, 3)])), 4);
+        // an L shape
+        assert_eq!(plotted_perimeter(&plot(&[(0, 0), (0, 1), (1, 1)])), 8);
+        // a 2x2 square
+        assert_eq!(
+            plotted_perimeter(&plot(&[(0, 0), (0, 1), (1, 0), (1, 1)])),
+            8
+        );
+        // diagonal neighbors don't share an edge
+        assert_eq!(plotted_perimeter(&plot(&[(0, 0), (1, 1)])), 8);
+    }
 }
@@ -1,3 +1,5 @@
+use crate::_2021::error::Aoc2021Error;
+
 pub fn parse_lantern_fish_histogram(input: &[usize]) -> Vec<u128> {
     input.iter().fold(vec![0; 9], |mut acc, &i| {
         acc[i] += 1;
@@ -5,21 +7,168 @@ pub fn parse_lantern_fish_histogram(input: &[usize]) -> Vec<u128> {
     })
 }
 
+fn advance_lantern_fish_day(hist: &mut [u128]) {
+    hist.rotate_left(1);
+    // Every 0 spawned exactly one fish. In other words, the number of new parents is equal to the
+    // number of new children.
+    // Parents should reset to 6 as opposed to new children being 8
+    //
+    // Therefore:
+    // 6 = the old 7's + the new parents
+    hist[6] += hist[8];
+}
+
 pub fn advance_lantern_fish_days(mut hist: Vec<u128>, days: i32) -> u128 {
     for _ in 0..days {
-        hist.rotate_left(1);
-        // Every 0 spawned exactly one fish. In other words, the number of new parents is equal to the
-        // number of new children.
-        // Parents should reset to 6 as opposed to new children being 8
-        //
-        // Therefore:
-        // 6 = the old 7's + the new parents
-        hist[6] += hist[8];
+        advance_lantern_fish_day(&mut hist);
     }
 
     hist.iter().sum()
 }
 
+/// Same as [`advance_lantern_fish_days`], but reports the day the population overflows instead
+/// of panicking.
+pub fn try_advance(mut hist: Vec<u128>, days: i32) -> Result<u128, Aoc2021Error> {
+    let checked_total = |hist: &[u128], day| {
+        hist.iter()
+            .try_fold(0, |total: u128, &count| total.checked_add(count))
+            .ok_or(Aoc2021Error::Overflow(day))
+    };
+    let mut total = checked_total(&hist, 0)?;
+    for day in 1..=days {
+        hist.rotate_left(1);
+        hist[6] = hist[6]
+            .checked_add(hist[8])
+            .ok_or(Aoc2021Error::Overflow(day))?;
+        total = checked_total(&hist, day)?;
+    }
+
+    Ok(total)
+}
+
+/// Same as [`advance_lantern_fish_days`] on a `u64` histogram, but returns `None` instead of
+/// overflowing.
+pub fn advance_lantern_fish_days_u64(mut hist: Vec<u64>, days: i32) -> Option<u64> {
+    for _ in 0..days {
+        hist.rotate_left(1);
+        hist[6] = hist[6].checked_add(hist[8])?;
+    }
+
+    // the population never shrinks, so only the final total needs checking
+    hist.iter()
+        .try_fold(0, |total: u64, &count| total.checked_add(count))
+}
+
+/// The first day on which there are at least twice as many fish as at the start. An empty school
+/// never grows, so it counts as doubled from day 0.
+pub fn doubling_days(mut hist: Vec<u128>) -> i32 {
+    let initial: u128 = hist.iter().sum();
+    let mut days = 0;
+    while hist.iter().sum::<u128>() < initial * 2 {
+        advance_lantern_fish_day(&mut hist);
+        days += 1;
+    }
+
+    days
+}
+
+/// Solves both parts with one simulation, recording the total at day 80 on the way to day 256.
+pub fn solve_day6(timers: &[usize]) -> (u128, u128) {
+    let mut hist = parse_lantern_fish_histogram(timers);
+    let mut after_80_days = 0;
+    for day in 1..=256 {
+        advance_lantern_fish_day(&mut hist);
+        if day == 80 {
+            after_80_days = hist.iter().sum();
+        }
+    }
+
+    (after_80_days, hist.iter().sum())
+}
+
+/// Like [`solve_day6`], but rejects a timer past 8 instead of panicking on the histogram.
+pub fn try_solve_day6(timers: &[usize]) -> Result<(u128, u128), Aoc2021Error> {
+    if let Some(&timer) = timers.iter().find(|&&timer| timer > 8) {
+        return Err(Aoc2021Error::InvalidTimer(timer));
+    }
+    Ok(solve_day6(timers))
+}
+
+/// A configurable lantern fish simulation. By default parents reset to 6 and newborns start at 8.
+///
+/// ```
+/// # use advent_of_code_rust::_2021::_06::LanternfishSim;
+/// let total = LanternfishSim::from_timers(&[3, 4, 3, 1, 2])
+///     .reset_at(6)
+///     .newborn_at(8)
+///     .advance(18);
+/// assert_eq!(total, 26);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LanternfishSim {
+    reset_at: u8,
+    newborn_at: u8,
+    timers: Vec<usize>,
+}
+
+impl Default for LanternfishSim {
+    fn default() -> Self {
+        LanternfishSim {
+            reset_at: 6,
+            newborn_at: 8,
+            timers: Vec::new(),
+        }
+    }
+}
+
+impl LanternfishSim {
+    pub fn from_timers(timers: &[usize]) -> Self {
+        LanternfishSim {
+            timers: timers.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn reset_at(mut self, timer: u8) -> Self {
+        self.reset_at = timer;
+        self
+    }
+
+    #[must_use]
+    pub fn newborn_at(mut self, timer: u8) -> Self {
+        self.newborn_at = timer;
+        self
+    }
+
+    pub fn advance(&self, days: i32) -> u128 {
+        let reset_at = usize::from(self.reset_at);
+        let newborn_at = usize::from(self.newborn_at);
+        let size = self
+            .timers
+            .iter()
+            .copied()
+            .chain([reset_at, newborn_at])
+            .max()
+            .unwrap_or_default()
+            + 1;
+        let mut hist = vec![0; size];
+        for &timer in &self.timers {
+            hist[timer] += 1;
+        }
+
+        for _ in 0..days {
+            let parents = hist[0];
+            hist.rotate_left(1);
+            hist[size - 1] = 0;
+            hist[reset_at] += parents;
+            hist[newborn_at] += parents;
+        }
+
+        hist.iter().sum()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +216,91 @@ mod tests {
 
         assert_eq!(total, 1_644_286_074_024);
     }
+
+    #[test]
+    fn test_lanternfish_sim_sample() {
+        let input = to_vec(Raw("3,4,3,1,2"), Comma);
+
+        let sim = LanternfishSim::from_timers(&input);
+
+        assert_eq!(sim.advance(80), 5_934);
+        assert_eq!(sim.advance(256), 26_984_457_539);
+        // parents that reset straight back to 0 double every day
+        assert_eq!(
+            LanternfishSim::from_timers(&[0])
+                .reset_at(0)
+                .newborn_at(0)
+                .advance(10),
+            1_024
+        );
+    }
+
+    #[test]
+    fn test_lanternfish_sim() {
+        let input = to_vec(Path("input/2021/06.txt"), Comma);
+
+        let total = LanternfishSim::from_timers(&input).advance(256);
+
+        assert_eq!(total, 1_644_286_074_024);
+    }
+
+    #[test]
+    fn test_solve_day6_sample() {
+        let input = to_vec(Raw("3,4,3,1,2"), Comma);
+
+        assert_eq!(solve_day6(&input), (5_934, 26_984_457_539));
+    }
+
+    #[test]
+    fn test_solve_day6() {
+        let input = to_vec(Path("input/2021/06.txt"), Comma);
+
+        assert_eq!(solve_day6(&input), (363_101, 1_644_286_074_024));
+    }
+
+    #[test]
+    fn test_doubling_days_sample() {
+        let input = to_vec(Raw("3,4,3,1,2"), Comma);
+        let hist = parse_lantern_fish_histogram(&input);
+
+        assert_eq!(doubling_days(hist.clone()), 5);
+        assert_eq!(advance_lantern_fish_days(hist.clone(), 4), 9);
+        assert_eq!(advance_lantern_fish_days(hist, 5), 10);
+        assert_eq!(doubling_days(vec![0; 9]), 0);
+    }
+
+    #[test]
+    fn test_advance_lantern_fish_days_u64() {
+        let input = to_vec(Raw("3,4,3,1,2"), Comma);
+        let hist = parse_lantern_fish_histogram(&input)
+            .into_iter()
+            .map(|count| count as u64)
+            .collect();
+
+        assert_eq!(advance_lantern_fish_days_u64(hist, 80), Some(5_934));
+
+        // the parents reset to 6 alongside the fish already at 7
+        let mut hist = vec![0; 9];
+        hist[0] = u64::MAX / 2 + 1;
+        hist[7] = u64::MAX / 2;
+        assert_eq!(
+            advance_lantern_fish_days_u64(hist.clone(), 0),
+            Some(u64::MAX)
+        );
+        assert_eq!(advance_lantern_fish_days_u64(hist, 1), None);
+    }
+
+    #[test]
+    fn test_try_advance() {
+        let input = to_vec(Raw("3,4,3,1,2"), Comma);
+        let hist = parse_lantern_fish_histogram(&input);
+
+        assert_eq!(try_advance(hist.clone(), 256), Ok(26_984_457_539));
+        assert_eq!(try_advance(hist, 2_000), Err(Aoc2021Error::Overflow(999)));
+
+        let mut hist = vec![0; 9];
+        hist[0] = u128::MAX / 2 + 1;
+        hist[7] = u128::MAX / 2;
+        assert_eq!(try_advance(hist, 3), Err(Aoc2021Error::Overflow(1)));
+    }
 }
@@ -1,3 +1,8 @@
+use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::cmp::Ordering;
+
 /// Calculates the Nth triangle number.
 ///
 /// A triangle number is the sum of all positive integers up to and including N.
@@ -22,7 +27,26 @@ pub fn triangle_number(n: i32) -> i32 {
 }
 pub fn find_cheapest_horizontal_position(crabs: &[i32], fuel_calculator: fn(i32) -> i32) -> i32 {
     let max_crab_pos = *crabs.iter().max().unwrap();
-    (0..max_crab_pos)
+    (0..=max_crab_pos)
+        .map(|horiz_pos| {
+            crabs
+                .iter()
+                .map(|&crab_pos| fuel_calculator((horiz_pos - crab_pos).abs()))
+                .sum()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Same as [`find_cheapest_horizontal_position`], but checks each position in parallel.
+#[cfg(feature = "parallel")]
+pub fn find_cheapest_horizontal_position_parallel(
+    crabs: &[i32],
+    fuel_calculator: fn(i32) -> i32,
+) -> i32 {
+    let max_crab_pos = *crabs.iter().max().unwrap();
+    (0..=max_crab_pos)
+        .into_par_iter()
         .map(|horiz_pos| {
             crabs
                 .iter()
@@ -32,6 +56,70 @@ pub fn find_cheapest_horizontal_position(crabs: &[i32], fuel_calculator: fn(i32)
         .min()
         .unwrap()
 }
+
+/// The total fuel for every crab to move to `position`, where `fuel` gives the cost of moving a
+/// single crab a given distance.
+pub fn fuel_cost_at(crabs: &[i32], position: i32, fuel: impl Fn(i32) -> i64) -> i64 {
+    crabs
+        .iter()
+        .map(|&crab_pos| fuel((position - crab_pos).abs()))
+        .sum()
+}
+
+/// The cheapest total fuel over every position between the outermost crabs.
+///
+/// `fuel` must be convex and non-decreasing in the distance, like linear or triangle fuel. The
+/// total is then convex in the position, so a ternary search only needs to evaluate O(log range)
+/// positions instead of all of them. For any other `fuel` the result may not be the minimum.
+pub fn cheapest_by_ternary_search(crabs: &[i32], fuel: impl Fn(i32) -> i64) -> i64 {
+    let cost = |position| fuel_cost_at(crabs, position, &fuel);
+    let (mut lo, mut hi) = crabs.iter().copied().minmax().into_option().unwrap();
+    while hi - lo > 2 {
+        let m1 = lo + (hi - lo) / 3;
+        let m2 = hi - (hi - lo) / 3;
+        match cost(m1).cmp(&cost(m2)) {
+            Ordering::Less => hi = m2 - 1,
+            Ordering::Greater => lo = m1 + 1,
+            // the minimum is between them, possibly on a flat stretch
+            Ordering::Equal => (lo, hi) = (m1, m2),
+        }
+    }
+    (lo..=hi).map(cost).min().unwrap()
+}
+
+/// The average crab position. The cheapest triangle fuel position is always within 1/2 of it.
+#[allow(clippy::cast_precision_loss)]
+pub fn mean_position(crabs: &[i32]) -> f64 {
+    crabs.iter().map(|&crab| f64::from(crab)).sum::<f64>() / crabs.len() as f64
+}
+
+/// The middle crab position (the lower one for an even count), which is a cheapest position for
+/// linear fuel.
+pub fn median_position(crabs: &[i32]) -> i32 {
+    let mut crabs = crabs.to_vec();
+    let middle = (crabs.len() - 1) / 2;
+    *crabs.select_nth_unstable(middle).1
+}
+
+/// Solves both parts from a single set of crab positions.
+///
+/// # Returns
+///
+/// The cheapest alignment using linear fuel, and the cheapest using triangle fuel
+pub fn solve_day7(crabs: &[i32]) -> (i32, i64) {
+    let linear = find_cheapest_horizontal_position(crabs, std::convert::identity);
+    // summed as i64 so the total can't overflow, unlike find_cheapest_horizontal_position
+    let triangle_fuel = |distance| {
+        let distance = i64::from(distance);
+        distance * (distance + 1) / 2
+    };
+    let max_crab_pos = *crabs.iter().max().unwrap();
+    let triangle = (0..=max_crab_pos)
+        .map(|position| fuel_cost_at(crabs, position, triangle_fuel))
+        .min()
+        .unwrap();
+    (linear, triangle)
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +158,112 @@ mod tests {
             98_231_647
         );
     }
+
+    #[test]
+    fn test_solve_day7_sample() {
+        let crabs = to_vec(Raw("16,1,2,0,4,2,7,1,2,14"), Comma);
+
+        assert_eq!(solve_day7(&crabs), (37, 168));
+        // each crab burns triangle_number(50_000), so the total doesn't fit in an i32
+        assert_eq!(solve_day7(&[0, 100_000]).1, 2_500_050_000);
+    }
+
+    #[test]
+    fn test_solve_day7() {
+        let crabs = to_vec(Path("input/2021/07.txt"), Comma);
+
+        assert_eq!(solve_day7(&crabs), (348_996, 98_231_647));
+    }
+
+    #[test]
+    fn test_rightmost_position() {
+        assert_eq!(find_cheapest_horizontal_position(&[5], identity), 0);
+        assert_eq!(find_cheapest_horizontal_position(&[3, 5, 5], identity), 2);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_sequential_sample() {
+        let crabs = to_vec(Raw("16,1,2,0,4,2,7,1,2,14"), Comma);
+
+        for fuel_calculator in [identity, triangle_number] {
+            assert_eq!(
+                find_cheapest_horizontal_position_parallel(&crabs, fuel_calculator),
+                find_cheapest_horizontal_position(&crabs, fuel_calculator)
+            );
+        }
+        assert_eq!(
+            find_cheapest_horizontal_position_parallel(&[5], identity),
+            0
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel() {
+        let crabs = to_vec(Path("input/2021/07.txt"), Comma);
+
+        assert_eq!(
+            find_cheapest_horizontal_position_parallel(&crabs, identity),
+            348_996
+        );
+        assert_eq!(
+            find_cheapest_horizontal_position_parallel(&crabs, triangle_number),
+            98_231_647
+        );
+    }
+
+    #[test]
+    fn test_mean_and_median_position_sample() {
+        let crabs = to_vec(Raw("16,1,2,0,4,2,7,1,2,14"), Comma);
+
+        assert_eq!(median_position(&crabs), 2);
+        assert!((mean_position(&crabs) - 4.9).abs() < f64::EPSILON);
+        assert_eq!(mean_position(&crabs).round() as i32, 5);
+    }
+
+    #[test]
+    fn test_fuel_cost_at_sample() {
+        let crabs = to_vec(Raw("16,1,2,0,4,2,7,1,2,14"), Comma);
+
+        assert_eq!(fuel_cost_at(&crabs, 2, i64::from), 37);
+        assert_eq!(fuel_cost_at(&crabs, 1, i64::from), 41);
+        assert_eq!(fuel_cost_at(&crabs, 10, i64::from), 71);
+        assert_eq!(
+            fuel_cost_at(&crabs, 5, |distance| i64::from(triangle_number(distance))),
+            168
+        );
+    }
+
+    #[test]
+    fn test_cheapest_by_ternary_search_sample() {
+        let crabs = to_vec(Raw("16,1,2,0,4,2,7,1,2,14"), Comma);
+
+        let triangle = |distance| i64::from(triangle_number(distance));
+        assert_eq!(cheapest_by_ternary_search(&crabs, i64::from), 37);
+        assert_eq!(cheapest_by_ternary_search(&crabs, triangle), 168);
+        assert_eq!(
+            cheapest_by_ternary_search(&crabs, i64::from),
+            i64::from(find_cheapest_horizontal_position(&crabs, identity))
+        );
+        // a flat stretch of equally cheap positions in the middle
+        assert_eq!(cheapest_by_ternary_search(&[0, 0, 10, 10], i64::from), 20);
+        assert_eq!(cheapest_by_ternary_search(&[5], i64::from), 0);
+    }
+
+    #[test]
+    fn test_cheapest_by_ternary_search() {
+        let crabs = to_vec(Path("input/2021/07.txt"), Comma);
+
+        let triangle = |distance| i64::from(triangle_number(distance));
+        assert_eq!(
+            cheapest_by_ternary_search(&crabs, i64::from),
+            i64::from(find_cheapest_horizontal_position(&crabs, identity))
+        );
+        assert_eq!(
+            cheapest_by_ternary_search(&crabs, triangle),
+            i64::from(find_cheapest_horizontal_position(&crabs, triangle_number))
+        );
+        assert_eq!(cheapest_by_ternary_search(&crabs, triangle), 98_231_647);
+    }
 }
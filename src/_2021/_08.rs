@@ -1,4 +1,6 @@
+use crate::_2021::error::Aoc2021Error;
 use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
 
 pub fn count_1478(input: impl Iterator<Item = String>) -> i32 {
     input
@@ -39,84 +41,227 @@ pub fn signal_to_mask(s: &str) -> u8 {
     mask
 }
 
+/// A lookup from segment labels to mask bits, for displays with other than seven segments.
+///
+/// Labels may be longer than one character; a signal is split by matching the longest label at
+/// each position. The first label gets the highest bit, so the default table matches
+/// [`DIGIT_MASKS`].
+pub struct SegmentTable {
+    masks: Vec<(String, u32)>,
+}
+
+impl SegmentTable {
+    pub fn new(labels: &[&str]) -> Self {
+        let width = labels.len();
+        assert!(width <= 32, "a u32 mask can't hold {width} segments");
+        // an empty label would match without consuming anything, so signal_to_mask never ends
+        assert!(
+            labels.iter().all(|label| !label.is_empty()),
+            "segment labels can't be empty"
+        );
+        assert!(
+            labels.iter().all_unique(),
+            "segment labels must be distinct"
+        );
+        let mut masks = labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| ((*label).to_string(), 1 << (width - 1 - i)))
+            .collect_vec();
+        // longest first, so a label is never shadowed by one of its own prefixes
+        masks.sort_by_key(|(label, _)| std::cmp::Reverse(label.len()));
+        SegmentTable { masks }
+    }
+
+    /// A table where every segment is labelled by a single character.
+    pub fn from_alphabet(alphabet: &str) -> Self {
+        let labels = alphabet.chars().map(String::from).collect_vec();
+        Self::new(&labels.iter().map(String::as_str).collect_vec())
+    }
+
+    pub fn width(&self) -> usize {
+        self.masks.len()
+    }
+
+    pub fn get_bit(&self, label: &str) -> u32 {
+        self.masks
+            .iter()
+            .find(|(l, _)| l == label)
+            .map_or(0, |(_, bit)| *bit)
+    }
+
+    /// Unknown characters are skipped, like [`signal_to_mask`] does.
+    pub fn signal_to_mask(&self, s: &str) -> u32 {
+        let mut mask = 0;
+        let mut rest = s;
+        while let Some(ch) = rest.chars().next() {
+            match self
+                .masks
+                .iter()
+                .find(|(label, _)| rest.starts_with(label.as_str()))
+            {
+                Some((label, bit)) => {
+                    mask |= bit;
+                    rest = &rest[label.len()..];
+                }
+                None => rest = &rest[ch.len_utf8()..],
+            }
+        }
+        mask
+    }
+}
+
+impl Default for SegmentTable {
+    fn default() -> Self {
+        SegmentTable {
+            masks: DIGIT_MASKS
+                .iter()
+                .map(|&(ch, bit)| (ch.to_string(), u32::from(bit)))
+                .collect(),
+        }
+    }
+}
+
 pub fn overlaps(a: u8, b: u8) -> bool {
+    mask_overlaps(u32::from(a), u32::from(b))
+}
+
+/// [`overlaps`] for the wider masks built by a [`SegmentTable`].
+fn mask_overlaps(a: u32, b: u32) -> bool {
     a & b == b
 }
 
-pub fn determine_output(row: &str) -> i32 {
-    let signals = row
+fn parse_row(table: &SegmentTable, row: &str) -> ([u32; 10], Vec<u32>) {
+    let masks = row
         .split_whitespace()
         .map(str::trim)
         .filter(|s| *s != "|")
-        .map(signal_to_mask)
+        .map(|signal| table.signal_to_mask(signal))
         .collect_vec();
-    let (signals, output) = signals.split_at(10);
+    let (signals, output) = masks.split_at(10);
+    let mut signals: [u32; 10] = signals.try_into().expect("row should have 10 signals");
+    // the order of the signals doesn't matter, so sorting lets equivalent rows share a key
+    signals.sort_unstable();
+    (signals, output.to_vec())
+}
 
+/// The mask of each digit, or `None` if the signals don't contain every digit.
+fn deduce_digit_masks(signals: &[u32; 10]) -> Option<[u32; 10]> {
     let mut digit_to_mask = [0; 10];
-    digit_to_mask[1] = *signals
-        .iter()
-        .find(|signal| signal.count_ones() == 2)
-        .unwrap();
-    digit_to_mask[4] = *signals
-        .iter()
-        .find(|signal| signal.count_ones() == 4)
-        .unwrap();
-    digit_to_mask[7] = *signals
-        .iter()
-        .find(|signal| signal.count_ones() == 3)
-        .unwrap();
-    digit_to_mask[8] = *signals
-        .iter()
-        .find(|signal| signal.count_ones() == 7)
-        .unwrap();
+    digit_to_mask[1] = *signals.iter().find(|signal| signal.count_ones() == 2)?;
+    digit_to_mask[4] = *signals.iter().find(|signal| signal.count_ones() == 4)?;
+    digit_to_mask[7] = *signals.iter().find(|signal| signal.count_ones() == 3)?;
+    digit_to_mask[8] = *signals.iter().find(|signal| signal.count_ones() == 7)?;
 
     digit_to_mask[3] = *signals
         .iter()
         .filter(|signal| signal.count_ones() == 5)
-        .find(|signal| overlaps(**signal, digit_to_mask[1]))
-        .unwrap();
+        .find(|signal| mask_overlaps(**signal, digit_to_mask[1]))?;
 
     digit_to_mask[9] = *signals
         .iter()
         .filter(|signal| signal.count_ones() == 6)
-        .find(|signal| overlaps(**signal, digit_to_mask[3]))
-        .unwrap();
+        .find(|signal| mask_overlaps(**signal, digit_to_mask[3]))?;
 
     digit_to_mask[0] = *signals
         .iter()
         .filter(|signal| signal.count_ones() == 6)
         .filter(|signal| **signal != digit_to_mask[9])
-        .filter(|signal| overlaps(**signal, digit_to_mask[7]))
-        .find(|signal| overlaps(**signal, digit_to_mask[1]))
-        .unwrap();
+        .filter(|signal| mask_overlaps(**signal, digit_to_mask[7]))
+        .find(|signal| mask_overlaps(**signal, digit_to_mask[1]))?;
 
     digit_to_mask[6] = *signals
         .iter()
         .filter(|signal| signal.count_ones() == 6)
         .filter(|signal| **signal != digit_to_mask[9])
-        .find(|signal| **signal != digit_to_mask[0])
-        .unwrap();
+        .find(|signal| **signal != digit_to_mask[0])?;
 
     digit_to_mask[5] = *signals
         .iter()
         .filter(|signal| signal.count_ones() == 5)
-        .find(|signal| overlaps(digit_to_mask[6], **signal))
-        .unwrap();
+        .find(|signal| mask_overlaps(digit_to_mask[6], **signal))?;
 
     digit_to_mask[2] = *signals
         .iter()
         .filter(|signal| signal.count_ones() == 5)
         .filter(|signal| **signal != digit_to_mask[5])
-        .find(|signal| **signal != digit_to_mask[3])
-        .unwrap();
+        .find(|signal| **signal != digit_to_mask[3])?;
+
+    Some(digit_to_mask)
+}
 
+fn decode_digits(digit_to_mask: &[u32; 10], output: &[u32]) -> Vec<u8> {
     output
         .iter()
-        .filter_map(|o| digit_to_mask.iter().find_position(|s| **s == *o))
-        .map(|i| i.0.to_string())
-        .collect::<String>()
-        .parse::<i32>()
-        .unwrap()
+        .filter_map(|o| digit_to_mask.iter().position(|s| s == o))
+        .map(|digit| digit as u8)
+        .collect()
+}
+
+fn decode_output(digit_to_mask: &[u32; 10], output: &[u32]) -> i32 {
+    decode_digits(digit_to_mask, output)
+        .into_iter()
+        .fold(0, |value, digit| value * 10 + i32::from(digit))
+}
+
+pub fn determine_output(row: &str) -> i32 {
+    determine_output_with(&SegmentTable::default(), row)
+}
+
+/// Like [`determine_output`], but reads the signals with a custom segment table.
+pub fn determine_output_with(table: &SegmentTable, row: &str) -> i32 {
+    let (signals, output) = parse_row(table, row);
+    decode_output(&deduce_digits(&signals), &output)
+}
+
+fn deduce_digits(signals: &[u32; 10]) -> [u32; 10] {
+    deduce_digit_masks(signals).expect("signals should contain every digit")
+}
+
+/// Like [`determine_output`], but fails on a row that isn't 10 signals, a `|`, and 4 output
+/// digits, or whose signals can't be decoded, instead of panicking.
+pub fn try_determine_output(row: &str) -> Result<i32, Aoc2021Error> {
+    let invalid = || Aoc2021Error::InvalidRow(row.to_owned());
+    let (signals, output) = row.split_once('|').ok_or_else(invalid)?;
+    if signals.split_whitespace().count() != 10 || output.split_whitespace().count() != 4 {
+        return Err(invalid());
+    }
+    let (signals, output) = parse_row(&SegmentTable::default(), row);
+    let digit_to_mask = deduce_digit_masks(&signals).ok_or_else(invalid)?;
+    if !output.iter().all(|digit| digit_to_mask.contains(digit)) {
+        return Err(invalid());
+    }
+    Ok(decode_output(&digit_to_mask, &output))
+}
+
+/// Sums the output of every row as an `i64`, so large inputs can't overflow the total.
+pub fn sum_outputs(lines: impl Iterator<Item = String>) -> i64 {
+    let table = SegmentTable::default();
+    lines
+        .map(|line| i64::from(determine_output_with(&table, &line)))
+        .sum()
+}
+
+/// The digits shown on a row's output, without repeats.
+pub fn distinct_decoded_digits(row: &str) -> HashSet<u8> {
+    let (signals, output) = parse_row(&SegmentTable::default(), row);
+    decode_digits(&deduce_digits(&signals), &output)
+        .into_iter()
+        .collect()
+}
+
+/// Sums the output of every row, only deducing the wiring once for each distinct set of signals.
+pub fn decode_rows_cached(rows: impl Iterator<Item = String>) -> i32 {
+    let table = SegmentTable::default();
+    let mut cache = HashMap::new();
+    rows.map(|row| {
+        let (signals, output) = parse_row(&table, &row);
+        let digit_to_mask = cache
+            .entry(signals)
+            .or_insert_with(|| deduce_digits(&signals));
+        decode_output(digit_to_mask, &output)
+    })
+    .sum()
 }
 #[cfg(test)]
 mod tests {
@@ -153,6 +298,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_segment_table() {
+        let seven = SegmentTable::default();
+        assert_eq!(seven.width(), 7);
+        assert_eq!(seven.signal_to_mask("cfbegad"), 0b0111_1111);
+        assert_eq!(
+            seven.signal_to_mask("dab"),
+            u32::from(signal_to_mask("dab"))
+        );
+        assert!(overlaps(signal_to_mask("dab"), signal_to_mask("ab")));
+        assert!(!overlaps(signal_to_mask("ab"), signal_to_mask("dab")));
+
+        let eight = SegmentTable::from_alphabet("abcdefgh");
+        assert_eq!(eight.width(), 8);
+        assert_eq!(eight.get_bit("a"), 0b1000_0000);
+        assert_eq!(eight.get_bit("h"), 0b0000_0001);
+        assert_eq!(eight.signal_to_mask("hab"), 0b1100_0001);
+        assert_eq!(eight.signal_to_mask("z"), 0);
+
+        let long = SegmentTable::new(&["s1", "s10", "dp"]);
+        assert_eq!(long.signal_to_mask("s10"), 0b010);
+        assert_eq!(long.signal_to_mask("dps1"), 0b101);
+    }
+
+    #[test]
+    #[should_panic(expected = "segment labels can't be empty")]
+    fn test_segment_table_empty_label() {
+        SegmentTable::new(&["a", ""]);
+    }
+
+    #[test]
+    #[should_panic(expected = "segment labels must be distinct")]
+    fn test_segment_table_duplicate_label() {
+        SegmentTable::from_alphabet("abca");
+    }
+
+    #[test]
+    fn test_determine_output_with() {
+        // the 5353 row from test_determine_output, with the `a` wire relabelled as `h`
+        let eight = SegmentTable::from_alphabet("abcdefgh");
+        assert_eq!(
+            determine_output_with(&eight, "hcedgfb cdfbe gcdfh fbchd dhb cefhbd cdfgeb ehfb chgedb hb | cdfeb fchdb cdfeb cdbhf"),
+            5353
+        );
+
+        let labelled = SegmentTable::new(&["top", "tl", "tr", "mid", "bl", "br", "bot"]);
+        assert_eq!(
+            determine_output_with(&labelled, "trbr toptrbr tltrmidbr toptltrmidblbrbot toptrmidblbot toptrmidbrbot toptlmidbrbot toptlmidblbrbot toptltrblbrbot toptltrmidbrbot | trbr toptrbr tltrmidbr toptltrblbrbot"),
+            1740
+        );
+    }
+
+    #[test]
+    fn test_distinct_decoded_digits() {
+        assert_eq!(
+            distinct_decoded_digits("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"),
+            HashSet::from([5, 3])
+        );
+        assert_eq!(
+            distinct_decoded_digits(
+                "fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg"
+            ),
+            HashSet::from([1, 9, 7])
+        );
+    }
+
     #[test]
     fn test_1_sample() {
         let input = Raw("
@@ -204,4 +415,65 @@ mod tests {
 
         assert_eq!(input.map(|l| determine_output(&l)).sum::<i32>(), 1_051_087);
     }
+
+    #[test]
+    fn test_decode_rows_cached_sample() {
+        let input = "
+        be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+        edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+        fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+        fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+        aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+        fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+        dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+        bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+        egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+        gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+        be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+        dbe fdcbe gdcfbea bgec fbcad dfegc dfgcae gbfdea edcgfb eb | gcbe cefbgd cefdb fdgacbe
+        ";
+
+        assert_eq!(
+            decode_rows_cached(to_lines(Raw(input))),
+            to_lines(Raw(input))
+                .map(|l| determine_output(&l))
+                .sum::<i32>()
+        );
+        assert_eq!(
+            decode_rows_cached(to_lines(Raw(input))),
+            61_229 + 8_394 + 4_938
+        );
+    }
+
+    #[test]
+    fn test_decode_rows_cached() {
+        let input = to_lines(Path("input/2021/08.txt"));
+
+        assert_eq!(decode_rows_cached(input), 1_051_087);
+    }
+
+    #[test]
+    fn test_sum_outputs_sample() {
+        let input = to_lines(Raw("
+        be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+        edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+        fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+        fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+        aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+        fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+        dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+        bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+        egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+        gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+        "));
+
+        assert_eq!(sum_outputs(input), 61_229);
+    }
+
+    #[test]
+    fn test_sum_outputs() {
+        let input = to_lines(Path("input/2021/08.txt"));
+
+        assert_eq!(sum_outputs(input), 1_051_087);
+    }
 }
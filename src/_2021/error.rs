@@ -0,0 +1,73 @@
+use std::fmt::{Display, Formatter};
+use std::num::ParseIntError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aoc2021Error {
+    ParseInt(ParseIntError),
+    InvalidBit(char),
+    InvalidCommand(String),
+    /// A line that doesn't have the shape the puzzle expects
+    InvalidRow(String),
+    /// A lantern fish timer past the newborn timer of 8
+    InvalidTimer(usize),
+    /// A bingo board with fewer than 5 rows
+    IncompleteBoard,
+    EmptyInput,
+    /// The simulation overflowed on the given day
+    Overflow(i32),
+}
+
+impl Display for Aoc2021Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ParseInt(e) => write!(f, "invalid number: {e}"),
+            Self::InvalidBit(c) => write!(f, "invalid bit: {c}"),
+            Self::InvalidCommand(s) => write!(f, "invalid command: {s}"),
+            Self::InvalidRow(s) => write!(f, "invalid row: {s}"),
+            Self::InvalidTimer(timer) => write!(f, "invalid timer: {timer}"),
+            Self::IncompleteBoard => write!(f, "board has fewer than 5 rows"),
+            Self::EmptyInput => write!(f, "input is empty"),
+            Self::Overflow(day) => write!(f, "overflowed on day {day}"),
+        }
+    }
+}
+
+impl std::error::Error for Aoc2021Error {}
+
+impl From<ParseIntError> for Aoc2021Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::ParseInt(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_2021::_02::Command;
+    use crate::_2021::_03::try_binary_str_to_decimal;
+
+    #[test]
+    fn test_parser_errors() {
+        assert!(matches!(
+            Command::try_parse("forward five"),
+            Err(Aoc2021Error::ParseInt(_))
+        ));
+        assert!(matches!(
+            Command::try_parse("backward 5"),
+            Err(Aoc2021Error::InvalidCommand(command)) if command == "backward"
+        ));
+        assert!(matches!(
+            Command::try_parse("  "),
+            Err(Aoc2021Error::EmptyInput)
+        ));
+        assert!(matches!(
+            try_binary_str_to_decimal("10201"),
+            Err(Aoc2021Error::InvalidBit('2'))
+        ));
+        assert!(matches!(
+            try_binary_str_to_decimal(""),
+            Err(Aoc2021Error::EmptyInput)
+        ));
+        assert_eq!(try_binary_str_to_decimal("10110"), Ok(22));
+    }
+}
@@ -6,3 +6,121 @@ pub mod _05;
 pub mod _06;
 pub mod _07;
 pub mod _08;
+pub mod error;
+
+use crate::grid::parse_segment;
+use crate::input_parsing::{to_lines, to_vec, Input, Separator::Comma};
+use crate::_2021::error::Aoc2021Error;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Solves both parts of `day`, formatted as strings.
+///
+/// The input is read once and checked before solving. Empty input, a token that isn't a number,
+/// a malformed line, and a bingo game nobody wins are returned as errors rather than panicking.
+pub fn run(day: u8, input: Input) -> Result<(String, String), String> {
+    let lines = || non_empty(to_lines(input).collect());
+    let answers = match day {
+        1 => {
+            let lines = lines()?;
+            parse_all::<i32>(&lines)?;
+            let (part1, part2) = _01::solve_day1_streaming(lines.into_iter());
+            (part1.to_string(), part2.to_string())
+        }
+        2 => {
+            let commands = lines()?
+                .iter()
+                .map(|line| _02::Command::try_parse(line))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            (
+                _02::calculate_distance(commands.clone()).to_string(),
+                _02::calculate_aim_and_distance(commands).to_string(),
+            )
+        }
+        3 => {
+            let report = lines()?;
+            for row in &report {
+                _03::try_binary_str_to_decimal(row).map_err(|e| e.to_string())?;
+            }
+            _03::report_width(&report)?;
+            let report = _03::analyze(report);
+            (
+                (report.gamma * report.epsilon).to_string(),
+                (report.oxygen * report.co2).to_string(),
+            )
+        }
+        4 => {
+            let (calls, boards) = _04::try_parse_calls_and_bingo_boards(lines()?.into_iter())
+                .map_err(|e| e.to_string())?;
+            let scores = _04::play_bingo(calls, boards);
+            let (Some(first), Some(last)) = (scores.first(), scores.last()) else {
+                return Err("no board wins".to_string());
+            };
+            (first.to_string(), last.to_string())
+        }
+        5 => {
+            let segments = lines()?
+                .into_iter()
+                .map(|line| parse_segment(&line).ok_or(Aoc2021Error::InvalidRow(line)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            let overlaps = |diagonals| {
+                let grid = _05::plot_points(segments.iter().copied(), diagonals);
+                _05::count_overlapping_points(grid).to_string()
+            };
+            (
+                overlaps(_05::Diagonals::Exclude),
+                overlaps(_05::Diagonals::Include),
+            )
+        }
+        6 => {
+            let timers = parse_all(&non_empty(comma_tokens(input))?)?;
+            let (part1, part2) = _06::try_solve_day6(&timers).map_err(|e| e.to_string())?;
+            (part1.to_string(), part2.to_string())
+        }
+        7 => {
+            let crabs = parse_all(&non_empty(comma_tokens(input))?)?;
+            let (part1, part2) = _07::solve_day7(&crabs);
+            (part1.to_string(), part2.to_string())
+        }
+        8 => {
+            let lines = lines()?;
+            let outputs = lines
+                .iter()
+                .map(|line| _08::try_determine_output(line).map(i64::from))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            (
+                _08::count_1478(lines.into_iter()).to_string(),
+                outputs.into_iter().sum::<i64>().to_string(),
+            )
+        }
+        _ => return Err(format!("2021 day {day} is not implemented")),
+    };
+    Ok(answers)
+}
+
+/// Every trimmed, non-blank comma separated token, unparsed so bad numbers can be reported.
+fn comma_tokens(input: Input) -> Vec<String> {
+    to_vec::<String>(input, Comma)
+        .into_iter()
+        .map(|token| token.trim().to_owned())
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+fn parse_all<T: FromStr<Err = ParseIntError>>(tokens: &[String]) -> Result<Vec<T>, String> {
+    tokens
+        .iter()
+        .map(|token| token.parse().map_err(|e| Aoc2021Error::from(e).to_string()))
+        .collect()
+}
+
+fn non_empty<T>(values: Vec<T>) -> Result<Vec<T>, String> {
+    if values.is_empty() {
+        Err(Aoc2021Error::EmptyInput.to_string())
+    } else {
+        Ok(values)
+    }
+}
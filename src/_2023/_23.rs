@@ -1,37 +1,48 @@
+use crate::grid::{astar, dijkstra, Direction, Point, Traversable};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Point {
-    x: i32,
-    y: i32,
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Tile {
+    Path,
+    Forest,
+    Slope(Direction),
 }
 
-impl Point {
-    fn neighbors(self) -> [Point; 4] {
-        [
-            Point {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Point {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Point {
-                x: self.x + 1,
-                y: self.y,
-            },
-            Point {
-                x: self.x - 1,
-                y: self.y,
-            },
-        ]
+impl Tile {
+    fn from_char(c: char) -> Result<Self, String> {
+        Ok(match c {
+            '.' => Self::Path,
+            '#' => Self::Forest,
+            '^' => Self::Slope(Direction::North),
+            'v' => Self::Slope(Direction::South),
+            '<' => Self::Slope(Direction::West),
+            '>' => Self::Slope(Direction::East),
+            e => return Err(format!("unknown char {e}")),
+        })
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Self::Path => '.',
+            Self::Forest => '#',
+            Self::Slope(Direction::North) => '^',
+            Self::Slope(Direction::South) => 'v',
+            Self::Slope(Direction::West) => '<',
+            Self::Slope(Direction::East) => '>',
+        }
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
     }
 }
 
 pub struct SnowIsland {
-    grid: HashMap<Point, char>,
+    grid: HashMap<Point, Tile>,
     height: i32,
     width: i32,
 }
@@ -40,25 +51,49 @@ impl FromStr for SnowIsland {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut height = 0;
-        let mut width = 0;
-        let grid = s
-            .lines()
+        Self::from_char_grid(s.lines().map(|line| line.chars().collect()).collect())
+    }
+}
+
+impl Traversable for SnowIsland {
+    type Node = Point;
+
+    fn start(&self) -> Point {
+        Self::START
+    }
+    fn goal(&self) -> Point {
+        Point {
+            x: self.width - 2,
+            y: self.height - 1,
+        }
+    }
+    fn neighbors(&self, point: Point) -> Vec<Point> {
+        self.valid_neighbors(point)
+    }
+}
+
+impl SnowIsland {
+    const START: Point = Point { x: 1, y: 0 };
+
+    /// Builds an island from rows of characters, the inverse of [`SnowIsland::to_char_grid`].
+    pub fn from_char_grid(grid: Vec<Vec<char>>) -> Result<SnowIsland, String> {
+        let height = grid.len() as i32;
+        let width = grid.iter().map(Vec::len).max().unwrap_or_default() as i32;
+        let grid = grid
+            .into_iter()
             .enumerate()
-            .flat_map(|(y, line)| {
-                height = height.max(y as i32 + 1);
-                width = width.max(line.len() as i32);
-                line.chars().enumerate().map(move |(x, c)| {
-                    (
+            .flat_map(|(y, row)| {
+                row.into_iter().enumerate().map(move |(x, c)| {
+                    Ok((
                         Point {
                             x: x as i32,
                             y: y as i32,
                         },
-                        c,
-                    )
+                        Tile::from_char(c)?,
+                    ))
                 })
             })
-            .collect::<HashMap<_, _>>();
+            .collect::<Result<HashMap<_, _>, String>>()?;
 
         Ok(SnowIsland {
             grid,
@@ -66,35 +101,253 @@ impl FromStr for SnowIsland {
             width,
         })
     }
-}
 
-impl SnowIsland {
-    pub fn longest_climbing_path(&self) -> usize {
-        0
+    fn is_walkable(&self, point: Point) -> bool {
+        !matches!(self.grid.get(&point), Some(Tile::Forest) | None)
     }
-    pub fn longest_path(&self) -> usize {
-        let start = Point { x: 1, y: 0 };
-        let goal = Point {
-            x: self.width - 2,
-            y: self.height - 1,
-        };
-
-        let path = self.dfs(start, goal).into_iter().collect::<HashSet<_>>();
+    /// The island as rows of characters, the same layout it was parsed from.
+    pub fn to_char_grid(&self) -> Vec<Vec<char>> {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .filter_map(|x| self.grid.get(&Point { x, y }))
+                    .map(ToString::to_string)
+                    .collect::<String>()
+                    .chars()
+                    .collect()
+            })
+            .collect()
+    }
+    /// The start, the goal, and every walkable tile where the trail branches,
+    /// in row-major order. Slopes are ignored when counting branches.
+    pub fn junctions(&self) -> Vec<Point> {
+        let start = Self::START;
+        let goal = self.goal();
+        let mut junctions = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let point = Point { x, y };
+                if !self.is_walkable(point) {
+                    continue;
+                }
+                if point == start || point == goal || self.walkable_neighbors(point).len() >= 3 {
+                    junctions.push(point);
+                }
+            }
+        }
+        junctions
+    }
+    /// Corridors between junctions as `(from, to, steps)`. Slopes are respected, so a
+    /// corridor that can only be walked one way only appears once.
+    pub fn edges(&self) -> Vec<(Point, Point, usize)> {
+        self.edges_with(Self::valid_neighbors)
+    }
+    fn edges_with(&self, neighbors: fn(&Self, Point) -> Vec<Point>) -> Vec<(Point, Point, usize)> {
+        let junctions = self.junctions();
+        let is_junction = junctions.iter().copied().collect::<HashSet<_>>();
+        let mut edges = Vec::new();
+        for &junction in &junctions {
+            for first_step in neighbors(self, junction) {
+                let mut previous = junction;
+                let mut current = first_step;
+                let mut steps = 1;
+                // follow the corridor until the next junction, or give up at a dead end
+                while !is_junction.contains(&current) {
+                    let Some(next) = neighbors(self, current)
+                        .into_iter()
+                        .find(|&next| next != previous)
+                    else {
+                        break;
+                    };
+                    previous = current;
+                    current = next;
+                    steps += 1;
+                }
+                if is_junction.contains(&current) {
+                    edges.push((junction, current, steps));
+                }
+            }
+        }
+        edges
+    }
+    /// The fewest steps from start to goal, respecting slopes.
+    pub fn shortest_path(&self) -> Option<usize> {
+        let goal = self.goal();
+        astar(
+            Self::START,
+            goal,
+            |point| {
+                self.valid_neighbors(point)
+                    .into_iter()
+                    .map(|neighbor| (neighbor, 1))
+            },
+            |point| point.manhattan_distance(goal),
+        )
+    }
+    /// The longest hike when slopes can be climbed like normal paths.
+    ///
+    /// Without slopes the trails form cycles, so a tile-by-tile search explodes. Instead the
+    /// corridors are contracted into a weighted graph between junctions and the longest path is
+    /// found with a DFS. The DFS keeps its own stack instead of recursing, so the depth of the
+    /// search is bounded by the heap rather than the thread's stack. Visited junctions are tracked
+    /// in a `u64` bitmask, which is plenty for the ~36 junctions in a real input.
+    pub fn longest_climbing_path(&self) -> usize {
+        let junctions = self.junctions();
+        assert!(junctions.len() <= 64, "too many junctions for a u64 mask");
+        let index = junctions
+            .iter()
+            .enumerate()
+            .map(|(i, &junction)| (junction, i))
+            .collect::<HashMap<_, _>>();
+        let mut adjacency = vec![Vec::new(); junctions.len()];
+        for (from, to, steps) in self.edges_with(Self::walkable_neighbors) {
+            adjacency[index[&from]].push((index[&to], steps));
+        }
 
-        // for y in 0..self.height {
-        //     for x in 0..self.width {
-        //         let point = &Point { x, y };
-        //         if path.contains(point) {
-        //             print!("O");
-        //         } else {
-        //             print!("{}", self.grid.get(point).unwrap());
-        //         }
-        //     }
-        //     println!();
-        // }
+        // junctions are always walkable, so a blocked start or goal means there is no hike
+        let (Some(&start), Some(&goal)) = (index.get(&Self::START), index.get(&self.goal())) else {
+            return 0;
+        };
+        let mut longest = 0;
+        let mut stack = vec![(start, 1_u64 << start, 0)];
+        // The goal sits at the end of a single corridor. Once its junction is reached the hike
+        // must head straight for the goal, or the goal would be cut off.
+        let last_junction = adjacency
+            .iter()
+            .position(|edges| edges.iter().any(|&(to, _)| to == goal));
+        while let Some((current, visited, steps)) = stack.pop() {
+            if current == goal {
+                longest = longest.max(steps);
+                continue;
+            }
+            if Some(current) == last_junction {
+                if let Some(&(_, edge_steps)) =
+                    adjacency[current].iter().find(|&&(to, _)| to == goal)
+                {
+                    longest = longest.max(steps + edge_steps);
+                }
+                continue;
+            }
+            for &(next, edge_steps) in &adjacency[current] {
+                if visited & (1 << next) == 0 {
+                    stack.push((next, visited | (1 << next), steps + edge_steps));
+                }
+            }
+        }
+        longest
+    }
+    /// The longest of the shortest routes between any two junctions, ignoring slopes.
+    pub fn junction_diameter(&self) -> usize {
+        let junctions = self.junctions();
+        let mut adjacency: HashMap<Point, Vec<(Point, usize)>> = HashMap::new();
+        for (from, to, steps) in self.edges_with(Self::walkable_neighbors) {
+            adjacency.entry(from).or_default().push((to, steps));
+        }
 
+        let mut diameter = 0;
+        for (i, &from) in junctions.iter().enumerate() {
+            for &to in &junctions[i + 1..] {
+                let distance = dijkstra(from, to, |junction| {
+                    adjacency.get(&junction).cloned().unwrap_or_default()
+                });
+                if let Some(distance) = distance {
+                    diameter = diameter.max(distance);
+                }
+            }
+        }
+        diameter
+    }
+    /// Every tile that isn't forest. No hike can take more than one step fewer than this.
+    pub fn walkable_tile_count(&self) -> usize {
+        self.grid
+            .values()
+            .filter(|tile| **tile != Tile::Forest)
+            .count()
+    }
+    /// Whether the goal can be reached from the start at all, ignoring slopes.
+    pub fn is_connected(&self) -> bool {
+        let goal = self.goal();
+        let mut visited = HashSet::from([Self::START]);
+        let mut queue = VecDeque::from([Self::START]);
+        while let Some(current) = queue.pop_front() {
+            if current == goal {
+                return true;
+            }
+            for neighbor in self.walkable_neighbors(current) {
+                if visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        false
+    }
+    /// The most tiles in one unbroken corridor, where every tile has exactly two walkable
+    /// neighbors. Junctions and dead ends end a corridor. Slopes are ignored.
+    pub fn longest_corridor(&self) -> usize {
+        let corridor = self
+            .grid
+            .keys()
+            .copied()
+            .filter(|&point| self.is_walkable(point) && self.walkable_neighbors(point).len() == 2)
+            .collect::<HashSet<_>>();
+        let mut visited = HashSet::new();
+        let mut longest = 0;
+        for &tile in &corridor {
+            if !visited.insert(tile) {
+                continue;
+            }
+            let mut length = 0;
+            let mut queue = VecDeque::from([tile]);
+            while let Some(current) = queue.pop_front() {
+                length += 1;
+                for neighbor in self.walkable_neighbors(current) {
+                    if corridor.contains(&neighbor) && visited.insert(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            longest = longest.max(length);
+        }
+        longest
+    }
+    pub fn longest_path(&self) -> usize {
+        let route = self.longest_path_route();
         // start doesn't count as taking a step
-        path.len() - 1
+        route.len().saturating_sub(1)
+    }
+    /// Every tile along the longest hike, from start to goal, or nothing if the goal can't be
+    /// reached.
+    pub fn longest_path_route(&self) -> Vec<Point> {
+        if !self.is_connected() {
+            return Vec::new();
+        }
+        self.dfs(Self::START, self.goal())
+    }
+    /// Draws the island with each tile on `path` shown as `O`.
+    pub fn render_path(&self, path: &[Point]) -> String {
+        let path = path.iter().collect::<HashSet<_>>();
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .filter_map(|x| {
+                        let point = Point { x, y };
+                        if path.contains(&point) {
+                            Some('O')
+                        } else {
+                            self.grid.get(&point).map(|tile| tile.to_char())
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// One drawing per tile of the longest hike, each revealing one more step of the route.
+    pub fn render_path_frames(&self) -> Vec<String> {
+        let route = self.longest_path_route();
+        (1..=route.len())
+            .map(|steps| self.render_path(&route[..steps]))
+            .collect()
     }
     fn dfs(&self, start: Point, goal: Point) -> Vec<Point> {
         let mut path_stack = VecDeque::new();
@@ -138,32 +391,24 @@ impl SnowIsland {
         }
         longest_path
     }
+    fn walkable_neighbors(&self, point: Point) -> Vec<Point> {
+        point
+            .neighbors()
+            .into_iter()
+            .filter(|neighbor| self.is_walkable(*neighbor))
+            .collect()
+    }
     fn valid_neighbors(&self, point: Point) -> Vec<Point> {
         let mut neighbors = Vec::new();
         for neighbor in point.neighbors() {
             match self.grid.get(&neighbor) {
-                Some('.') => neighbors.push(neighbor),
-                Some('>') => {
-                    if neighbor.x == point.x + 1 {
-                        neighbors.push(neighbor);
-                    }
-                }
-                Some('<') => {
-                    if neighbor.x == point.x - 1 {
-                        neighbors.push(neighbor);
-                    }
-                }
-                Some('v') => {
-                    if neighbor.y == point.y + 1 {
-                        neighbors.push(neighbor);
-                    }
-                }
-                Some('^') => {
-                    if neighbor.y == point.y - 1 {
+                Some(Tile::Path) => neighbors.push(neighbor),
+                Some(Tile::Slope(direction)) => {
+                    if neighbor == point.step(*direction) {
                         neighbors.push(neighbor);
                     }
                 }
-                Some('#' | _) | None => (),
+                Some(Tile::Forest) | None => (),
             }
         }
 
@@ -216,18 +461,268 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
+    fn test_junctions_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let junctions = island.junctions();
+
+        // 7 branching tiles plus the start and goal
+        assert_eq!(junctions.len(), 9);
+        assert!(junctions.contains(&Point { x: 1, y: 0 }));
+        assert!(junctions.contains(&Point { x: 21, y: 22 }));
+    }
+
+    #[test]
+    fn test_edges_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let edges = island.edges();
+
+        assert!(edges.contains(&(Point { x: 1, y: 0 }, Point { x: 3, y: 5 }, 15)));
+        assert!(edges.contains(&(Point { x: 19, y: 19 }, Point { x: 21, y: 22 }, 5)));
+        // slopes make the corridor one-way
+        assert!(!edges.contains(&(Point { x: 3, y: 5 }, Point { x: 1, y: 0 }, 15)));
+    }
+
+    #[test]
+    fn test_shortest_path_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let neighbors = |point| {
+            island
+                .valid_neighbors(point)
+                .into_iter()
+                .map(|neighbor| (neighbor, 1))
+        };
+        let expected = dijkstra(SnowIsland::START, island.goal(), neighbors);
+
+        assert!(expected.is_some());
+        assert_eq!(island.shortest_path(), expected);
+    }
+
+    #[test]
+    fn test_to_char_grid_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let grid = island.to_char_grid();
+
+        assert_eq!(grid.len(), 23);
+        assert!(grid.iter().all(|row| row.len() == 23));
+        assert_eq!(grid[0][1], '.');
+        assert_eq!(grid[3][10], '>');
+        assert_eq!(grid[4][3], 'v');
+        assert_eq!(
+            grid,
+            SAMPLE
+                .lines()
+                .map(|line| line.chars().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_from_char_grid_sample() {
+        let grid = SAMPLE
+            .lines()
+            .map(|line| line.chars().collect())
+            .collect::<Vec<Vec<char>>>();
+
+        let island = SnowIsland::from_char_grid(grid.clone()).unwrap();
+
+        assert_eq!(island.to_char_grid(), grid);
+        assert_eq!(island.longest_path(), 94);
+        assert_eq!(
+            SnowIsland::from_char_grid(vec![vec!['#', '?']]).err(),
+            Some("unknown char ?".to_string())
+        );
+        assert_eq!(
+            "#?".parse::<SnowIsland>().err(),
+            Some("unknown char ?".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_connected() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+        assert!(island.is_connected());
+
+        let island: SnowIsland = "\
+#.###
+#.#.#
+###.#"
+            .parse()
+            .unwrap();
+        assert!(!island.is_connected());
+        assert_eq!(island.longest_path(), 0);
+    }
+
+    #[test]
+    fn test_longest_corridor() {
+        // the junctions at (1,1) and (5,3) split the loop into two corridors of 5 tiles
+        let island: SnowIsland = "\
+#.#####
+#.....#
+#.###.#
+#.....#
+#####.#"
+            .parse()
+            .unwrap();
+        assert_eq!(island.longest_corridor(), 5);
+
+        let island: SnowIsland = "\
+#.#####
+#.....#
+#####.#"
+            .parse()
+            .unwrap();
+        assert_eq!(island.longest_corridor(), 5);
+    }
+
+    #[test]
+    fn test_render_path_frames_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let route = island.longest_path_route();
+        let frames = island.render_path_frames();
+
+        assert_eq!(frames.len(), route.len());
+        assert_eq!(route.len(), 95);
+        assert_eq!(frames[0].matches('O').count(), 1);
+        assert_eq!(frames.last().unwrap().matches('O').count(), 95);
+        assert_eq!(frames.last(), Some(&island.render_path(&route)));
+        assert!(frames[0].starts_with("#O#"));
+    }
+
+    #[test]
+    fn test_walkable_tile_count_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let count = island.walkable_tile_count();
+
+        assert_eq!(
+            count,
+            SAMPLE.chars().filter(|&c| ".<>^v".contains(c)).count()
+        );
+        assert!(count > 94);
+        assert!(count > island.longest_climbing_path());
+    }
+
+    #[test]
+    fn test_slope_direction() {
+        for (c, direction) in [
+            ('^', Direction::North),
+            ('v', Direction::South),
+            ('<', Direction::West),
+            ('>', Direction::East),
+        ] {
+            assert_eq!(Tile::from_char(c), Ok(Tile::Slope(direction)));
+        }
+    }
+
+    #[test]
+    fn test_traversable_longest_path_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        assert_eq!(crate::grid::longest_path(&island), 94);
+    }
+
+    #[test]
     fn test_2_sample() {
         let island: SnowIsland = SAMPLE.parse().unwrap();
 
-        assert_eq!(island.longest_path(), 154);
+        assert_eq!(island.longest_climbing_path(), 154);
+    }
+
+    #[test]
+    fn test_2_unreachable_goal() {
+        let island: SnowIsland = "\
+#.###
+#.#.#
+#####"
+            .parse()
+            .unwrap();
+
+        assert_eq!(island.longest_path(), 0);
+        assert_eq!(island.longest_climbing_path(), 0);
+    }
+
+    #[test]
+    fn test_junction_diameter_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        let diameter = island.junction_diameter();
+        let undirected = dijkstra(SnowIsland::START, island.goal(), |point| {
+            island
+                .walkable_neighbors(point)
+                .into_iter()
+                .map(|neighbor| (neighbor, 1))
+        });
+        assert!(diameter >= undirected.unwrap());
+        assert!(diameter <= island.longest_climbing_path());
+        assert_eq!(diameter, 74);
+    }
+
+    /// The island with every slope treated as a plain path, walked tile by tile.
+    struct Climbing<'a>(&'a SnowIsland);
+
+    impl Traversable for Climbing<'_> {
+        type Node = Point;
+
+        fn start(&self) -> Point {
+            self.0.start()
+        }
+        fn goal(&self) -> Point {
+            self.0.goal()
+        }
+        fn neighbors(&self, point: Point) -> Vec<Point> {
+            self.0.walkable_neighbors(point)
+        }
+    }
+
+    /// Tries every route through the junction graph, without pruning at the last junction.
+    fn exhaustive_climb(island: &SnowIsland) -> usize {
+        fn climb(
+            edges: &[(Point, Point, usize)],
+            current: Point,
+            goal: Point,
+            visited: &mut HashSet<Point>,
+        ) -> Option<usize> {
+            if current == goal {
+                return Some(0);
+            }
+            let mut longest = None;
+            for &(_, next, steps) in edges.iter().filter(|(from, _, _)| *from == current) {
+                if visited.insert(next) {
+                    if let Some(rest) = climb(edges, next, goal, visited) {
+                        longest = longest.max(Some(steps + rest));
+                    }
+                    visited.remove(&next);
+                }
+            }
+            longest
+        }
+
+        let edges = island.edges_with(SnowIsland::walkable_neighbors);
+        let mut visited = HashSet::from([SnowIsland::START]);
+        climb(&edges, SnowIsland::START, island.goal(), &mut visited).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_2_cross_check_sample() {
+        let island: SnowIsland = SAMPLE.parse().unwrap();
+
+        assert_eq!(crate::grid::longest_path(&Climbing(&island)), 154);
+        assert_eq!(exhaustive_climb(&island), 154);
     }
 
     #[test]
-    #[ignore]
     fn test_2() {
         let island: SnowIsland = INPUT.parse().unwrap();
 
-        assert_eq!(island.longest_path(), 1 + 1);
+        let longest = island.longest_climbing_path();
+
+        // climbing slopes can only lengthen part 1's hike, and no hike revisits a tile
+        assert!(longest >= 2_334);
+        assert!(longest < island.walkable_tile_count());
     }
 }
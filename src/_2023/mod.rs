@@ -23,3 +23,24 @@ pub mod _22;
 pub mod _23;
 pub mod _24;
 pub mod _25;
+
+use crate::input_parsing::{to_lines, Input};
+use itertools::Itertools;
+
+/// Solves both parts of `day`, formatted as strings. Only day 23 is wired up so far.
+pub fn run(day: u8, input: Input) -> Result<(String, String), String> {
+    match day {
+        23 => {
+            let lines = to_lines(input).collect_vec();
+            if lines.is_empty() {
+                return Err("input is empty".to_string());
+            }
+            let island: _23::SnowIsland = lines.join("\n").parse()?;
+            Ok((
+                island.longest_path().to_string(),
+                island.longest_climbing_path().to_string(),
+            ))
+        }
+        _ => Err(format!("2023 day {day} isn't wired up to run yet")),
+    }
+}
@@ -0,0 +1,360 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    /// The four points one step north, south, east and west.
+    pub fn neighbors(self) -> [Point; 4] {
+        [
+            Direction::North,
+            Direction::South,
+            Direction::East,
+            Direction::West,
+        ]
+        .map(|direction| self.step(direction))
+    }
+
+    /// The point one step away in `direction`.
+    #[must_use]
+    pub fn step(self, direction: Direction) -> Point {
+        let (dx, dy) = direction.delta();
+        Point {
+            x: self.x + dx,
+            y: self.y + dy,
+        }
+    }
+
+    pub fn manhattan_distance(self, other: Point) -> usize {
+        (self.x.abs_diff(other.x) + self.y.abs_diff(other.y)) as usize
+    }
+}
+
+/// Parses `"x,y"`, allowing whitespace around either number.
+pub fn parse_point(s: &str) -> Option<Point> {
+    let (x, y) = s.split_once(',')?;
+    Some(Point {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+    })
+}
+
+/// Parses `"x y"`, two numbers separated by any whitespace and nothing else.
+pub fn parse_point_ws(s: &str) -> Option<Point> {
+    let mut coordinates = s.split_whitespace();
+    let x = coordinates.next()?.parse().ok()?;
+    let y = coordinates.next()?.parse().ok()?;
+    if coordinates.next().is_some() {
+        return None;
+    }
+    Some(Point { x, y })
+}
+
+/// Parses a line segment written as `"x,y -> x,y"`.
+pub fn parse_segment(line: &str) -> Option<(Point, Point)> {
+    let (start, end) = line.split_once("->")?;
+    Some((parse_point(start)?, parse_point(end)?))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    /// One step in this direction as `(dx, dy)`, with y growing downwards like the puzzle grids.
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Self::North => (0, -1),
+            Self::South => (0, 1),
+            Self::East => (1, 0),
+            Self::West => (-1, 0),
+        }
+    }
+}
+
+/// Parses a grid of single digits, such as `"123\n456"`, keyed by each digit's position.
+/// Anything that isn't a digit is skipped.
+pub fn parse_digit_grid(s: &str) -> HashMap<Point, u32> {
+    s.lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars().enumerate().filter_map(move |(x, c)| {
+                let point = Point {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                Some((point, c.to_digit(10)?))
+            })
+        })
+        .collect()
+}
+
+/// Every point in the box from `min` to `max` inclusive, row by row.
+pub fn points_in_rect(min: Point, max: Point) -> impl Iterator<Item = Point> {
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Point { x, y }))
+}
+
+/// The point `(dx, dy)` away from `p` on a `width` by `height` grid whose edges wrap around to
+/// the opposite side.
+pub fn wrapping_neighbor(p: Point, dx: i32, dy: i32, width: i32, height: i32) -> Point {
+    Point {
+        x: (p.x + dx).rem_euclid(width),
+        y: (p.y + dy).rem_euclid(height),
+    }
+}
+
+/// A puzzle that can be walked from a start node to a goal node.
+pub trait Traversable {
+    type Node: Eq + Hash + Copy;
+
+    fn start(&self) -> Self::Node;
+    fn goal(&self) -> Self::Node;
+    fn neighbors(&self, node: Self::Node) -> Vec<Self::Node>;
+}
+
+/// The most steps it takes to reach the goal from the start without revisiting a node, or 0 if
+/// the goal can't be reached. This tries every simple path, so it is only practical when there
+/// are few branches.
+pub fn longest_path<T: Traversable>(graph: &T) -> usize {
+    let start = graph.start();
+    let goal = graph.goal();
+    let mut longest = 0;
+    let mut stack = vec![(start, HashSet::from([start]), 0)];
+
+    while let Some((current, visited, steps)) = stack.pop() {
+        if current == goal {
+            longest = longest.max(steps);
+            continue;
+        }
+        let mut next_nodes = graph
+            .neighbors(current)
+            .into_iter()
+            .filter(|next| !visited.contains(next))
+            .collect::<Vec<_>>();
+        // only clone the visited set when the path branches
+        let Some(last) = next_nodes.pop() else {
+            continue;
+        };
+        for next in next_nodes {
+            let mut visited = visited.clone();
+            visited.insert(next);
+            stack.push((next, visited, steps + 1));
+        }
+        let mut visited = visited;
+        visited.insert(last);
+        stack.push((last, visited, steps + 1));
+    }
+
+    longest
+}
+
+struct State<N> {
+    node: N,
+    cost: usize,
+    priority: usize,
+}
+
+impl<N> PartialEq for State<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N> Eq for State<N> {}
+
+// Flip the ordering to make BinaryHeap a min-heap/priority queue
+impl<N> Ord for State<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<N> PartialOrd for State<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds the cheapest cost from `start` to `goal`.
+///
+/// `neighbors` yields each reachable node along with the cost of stepping to it.
+/// `heuristic` must never overestimate the remaining cost to `goal`, otherwise the
+/// result may not be the cheapest.
+pub fn astar<N, I>(
+    start: N,
+    goal: N,
+    neighbors: impl Fn(N) -> I,
+    heuristic: impl Fn(N) -> usize,
+) -> Option<usize>
+where
+    N: Eq + Hash + Copy,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut priority_queue = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+
+    best_costs.insert(start, 0);
+    priority_queue.push(State {
+        node: start,
+        cost: 0,
+        priority: heuristic(start),
+    });
+
+    while let Some(State { node, cost, .. }) = priority_queue.pop() {
+        if node == goal {
+            return Some(cost);
+        }
+        if best_costs.get(&node).is_some_and(|&best| cost > best) {
+            continue;
+        }
+        for (neighbor, step_cost) in neighbors(node) {
+            let next_cost = cost + step_cost;
+            if best_costs
+                .get(&neighbor)
+                .is_none_or(|&best| next_cost < best)
+            {
+                best_costs.insert(neighbor, next_cost);
+                priority_queue.push(State {
+                    node: neighbor,
+                    cost: next_cost,
+                    priority: next_cost + heuristic(neighbor),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest cost from `start` to `goal`. This is [`astar`] without a heuristic.
+pub fn dijkstra<N, I>(start: N, goal: N, neighbors: impl Fn(N) -> I) -> Option<usize>
+where
+    N: Eq + Hash + Copy,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    astar(start, goal, neighbors, |_| 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dijkstra_weighted() {
+        // 0 -> 1 -> 3 is cheaper than 0 -> 3 directly
+        let edges = |node: u8| match node {
+            0 => vec![(1, 1), (2, 4), (3, 10)],
+            1 => vec![(3, 2)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        };
+
+        assert_eq!(dijkstra(0, 3, edges), Some(3));
+        assert_eq!(dijkstra(3, 0, edges), None);
+    }
+
+    #[test]
+    fn test_parse_segment() {
+        assert_eq!(
+            parse_segment("0,9 -> 5,9"),
+            Some((Point { x: 0, y: 9 }, Point { x: 5, y: 9 }))
+        );
+        assert_eq!(parse_segment("0,9"), None);
+        assert_eq!(parse_segment("0,9 -> five,9"), None);
+    }
+
+    #[test]
+    fn test_parse_point_ws() {
+        assert_eq!(parse_point_ws("3 4"), Some(Point { x: 3, y: 4 }));
+        assert_eq!(parse_point_ws("  3\t4 "), Some(Point { x: 3, y: 4 }));
+        assert_eq!(parse_point_ws("3"), None);
+        assert_eq!(parse_point_ws("3 4 5"), None);
+    }
+
+    #[test]
+    fn test_point_step() {
+        let point = Point { x: 3, y: 4 };
+
+        assert_eq!(point.step(Direction::North), Point { x: 3, y: 3 });
+        assert_eq!(point.step(Direction::West), Point { x: 2, y: 4 });
+        assert_eq!(point.manhattan_distance(Point { x: 0, y: 6 }), 5);
+    }
+
+    #[test]
+    fn test_direction_delta() {
+        assert_eq!(Direction::North.delta(), (0, -1));
+        assert_eq!(Direction::South.delta(), (0, 1));
+        assert_eq!(Direction::East.delta(), (1, 0));
+        assert_eq!(Direction::West.delta(), (-1, 0));
+    }
+
+    #[test]
+    fn test_parse_digit_grid() {
+        let grid = parse_digit_grid("2199943210\n3987894921\n9856789892");
+
+        assert_eq!(grid.len(), 30);
+        assert_eq!(grid[&Point { x: 0, y: 0 }], 2);
+        assert_eq!(grid[&Point { x: 9, y: 0 }], 0);
+        assert_eq!(grid[&Point { x: 2, y: 2 }], 5);
+        assert_eq!(grid.get(&Point { x: 10, y: 0 }), None);
+    }
+
+    #[test]
+    fn test_points_in_rect() {
+        let points = points_in_rect(Point { x: 1, y: 2 }, Point { x: 3, y: 3 }).collect::<Vec<_>>();
+
+        assert_eq!(points.len(), 6);
+        assert_eq!(points.first(), Some(&Point { x: 1, y: 2 }));
+        assert_eq!(points[3], Point { x: 1, y: 3 });
+        assert_eq!(points.last(), Some(&Point { x: 3, y: 3 }));
+        assert_eq!(
+            points_in_rect(Point { x: 1, y: 1 }, Point { x: 0, y: 0 }).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_wrapping_neighbor() {
+        let (width, height) = (10, 9);
+
+        // right edge -> left edge
+        assert_eq!(
+            wrapping_neighbor(Point { x: 9, y: 4 }, 1, 0, width, height),
+            Point { x: 0, y: 4 }
+        );
+        // left edge -> right edge
+        assert_eq!(
+            wrapping_neighbor(Point { x: 0, y: 4 }, -1, 0, width, height),
+            Point { x: 9, y: 4 }
+        );
+        // bottom edge -> top edge
+        assert_eq!(
+            wrapping_neighbor(Point { x: 3, y: 8 }, 0, 1, width, height),
+            Point { x: 3, y: 0 }
+        );
+        // top edge -> bottom edge
+        assert_eq!(
+            wrapping_neighbor(Point { x: 3, y: 0 }, 0, -1, width, height),
+            Point { x: 3, y: 8 }
+        );
+        // corner, both at once
+        assert_eq!(
+            wrapping_neighbor(Point { x: 9, y: 8 }, 1, 1, width, height),
+            Point { x: 0, y: 0 }
+        );
+        // no wrapping needed
+        assert_eq!(
+            wrapping_neighbor(Point { x: 4, y: 4 }, 1, -1, width, height),
+            Point { x: 5, y: 3 }
+        );
+    }
+}
@@ -1,14 +1,21 @@
-use crate::input_parsing::{Input::*, Separator::*};
+use crate::input_parsing::{
+    Input::{Path, Raw},
+    Separator::{Comma, Newline},
+};
 use itertools::Itertools;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 #[derive(Copy, Clone)]
 pub enum Input<'a> {
     Path(&'a str),
     Raw(&'a str),
+    /// A gzip-compressed file, decompressed as it is read
+    #[cfg(feature = "flate2")]
+    GzPath(&'a str),
 }
 
 #[derive(Copy, Clone)]
@@ -17,18 +24,35 @@ pub enum Separator {
     Newline,
 }
 
+/// Builds the path to a puzzle input relative to the crate root rather than the
+/// current working directory, e.g. `input/2021/01.txt`.
+pub fn input_path(year: u16, day: u8) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("input")
+        .join(year.to_string())
+        .join(format!("{day:02}.txt"))
+}
+
+fn reader_to_lines<'a>(reader: impl Read + 'a) -> Box<dyn Iterator<Item = String> + 'a> {
+    Box::new(
+        BufReader::new(reader)
+            .lines()
+            .map_while(Result::ok)
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty()),
+    )
+}
+
 pub fn to_lines(input: Input) -> Box<dyn Iterator<Item = String> + '_> {
     match input {
         Path(path) => {
             let file = File::open(path).expect("Failed to open file");
-            let reader = BufReader::new(file);
-            Box::new(
-                reader
-                    .lines()
-                    .map_while(Result::ok)
-                    .map(|s| s.trim().to_owned())
-                    .filter(|s| !s.is_empty()),
-            )
+            reader_to_lines(file)
+        }
+        #[cfg(feature = "flate2")]
+        Input::GzPath(path) => {
+            let file = File::open(path).expect("Failed to open file");
+            reader_to_lines(flate2::read::GzDecoder::new(file))
         }
         Raw(s) => Box::new(
             s.lines()
@@ -38,6 +62,11 @@ pub fn to_lines(input: Input) -> Box<dyn Iterator<Item = String> + '_> {
     }
 }
 
+/// The number of non-blank lines, as seen by [`to_lines`].
+pub fn line_count(input: Input) -> usize {
+    to_lines(input).count()
+}
+
 pub fn to_vec<T>(input: Input, delim: Separator) -> Vec<T>
 where
     T: FromStr,
@@ -50,6 +79,15 @@ where
             file.read_to_string(&mut str).unwrap_or_default();
             str
         }
+        #[cfg(feature = "flate2")]
+        Input::GzPath(path) => {
+            let file = File::open(path).unwrap();
+            let mut str = String::new();
+            flate2::read::GzDecoder::new(file)
+                .read_to_string(&mut str)
+                .unwrap_or_default();
+            str
+        }
         Raw(s) => s.to_string(),
     };
     let string_parser = |s: &str| s.parse::<T>().ok();
@@ -62,3 +100,56 @@ where
         Comma => str.split(',').filter_map(string_parser).collect_vec(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_path() {
+        let path = input_path(2021, 1);
+
+        assert!(path.is_absolute());
+        assert!(path.ends_with("input/2021/01.txt"));
+    }
+
+    #[test]
+    fn test_line_count() {
+        let input = Raw("
+        forward 5
+
+        down 5
+           
+        up 3
+        ");
+
+        assert_eq!(line_count(input), 3);
+        assert_eq!(line_count(Raw("")), 0);
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_gz_path() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let write_gz = |name: &str, contents: &[u8]| {
+            let path = std::env::temp_dir().join(format!("{}_{name}", std::process::id()));
+            let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+            encoder.write_all(contents).unwrap();
+            encoder.finish().unwrap();
+            path
+        };
+        let lines_path = write_gz("lines.txt.gz", b"199\n  200\n\n208\n");
+        let crabs_path = write_gz("crabs.txt.gz", b"16,1,2,0,4,2,7,1,2,14");
+
+        let lines: Vec<String> = to_lines(Input::GzPath(lines_path.to_str().unwrap())).collect();
+        let crabs: Vec<i32> = to_vec(Input::GzPath(crabs_path.to_str().unwrap()), Comma);
+
+        std::fs::remove_file(lines_path).unwrap();
+        std::fs::remove_file(crabs_path).unwrap();
+        assert_eq!(lines, vec!["199", "200", "208"]);
+        assert_eq!(crabs, vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The most common value at each index across all rows. Rows may have different lengths; each
+/// position only counts the rows long enough to reach it. Ties go to the value seen first.
+pub fn most_common_per_position<T: Eq + Hash + Clone>(rows: &[Vec<T>]) -> Vec<T> {
+    most_common_per_position_by(rows, |_, _| Ordering::Equal)
+}
+
+/// Same as [`most_common_per_position`], but ties go to the greatest value according to
+/// `tie_break`, and only fall back to the value seen first if `tie_break` can't separate them.
+pub fn most_common_per_position_by<T, F>(rows: &[Vec<T>], mut tie_break: F) -> Vec<T>
+where
+    T: Eq + Hash + Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+    (0..width)
+        .map(|position| {
+            let mut counts = HashMap::new();
+            let mut first_seen = Vec::new();
+            for value in rows.iter().filter_map(|row| row.get(position)) {
+                let count = counts.entry(value).or_insert(0);
+                if *count == 0 {
+                    first_seen.push(value);
+                }
+                *count += 1;
+            }
+            // max_by keeps the last of equal elements, so search backwards to favor the first
+            first_seen
+                .into_iter()
+                .rev()
+                .max_by(|a, b| counts[a].cmp(&counts[b]).then_with(|| tie_break(a, b)))
+                .expect("every position is reached by at least one row")
+                .clone()
+        })
+        .collect()
+}
+
+/// How many elements are greater than the one before them.
+pub fn count_ascending<I, T>(iter: I) -> usize
+where
+    I: Iterator<Item = T>,
+    T: PartialOrd,
+{
+    let mut count = 0;
+    let mut previous = None;
+    for value in iter {
+        if previous.as_ref().is_some_and(|previous| *previous < value) {
+            count += 1;
+        }
+        previous = Some(value);
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_common_per_position() {
+        let rows: Vec<Vec<char>> = ["abc", "abd", "xbd", "ab"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+
+        assert_eq!(most_common_per_position(&rows), vec!['a', 'b', 'd']);
+    }
+
+    #[test]
+    fn test_most_common_per_position_ties() {
+        let rows = vec![vec![2, 1], vec![1, 2]];
+
+        assert_eq!(most_common_per_position(&rows), vec![2, 1]);
+        assert_eq!(most_common_per_position::<u8>(&[]), Vec::<u8>::new());
+        assert_eq!(
+            most_common_per_position_by(&rows, |a: &u8, b: &u8| b.cmp(a)),
+            vec![1, 1]
+        );
+    }
+
+    #[test]
+    fn test_count_ascending() {
+        assert_eq!(count_ascending([1, 3, 2, 4, 4, 5].into_iter()), 3);
+        assert_eq!(
+            count_ascending([0.5, 0.25, 1.5, f64::NAN, 2.0].into_iter()),
+            1
+        );
+        assert_eq!(
+            count_ascending(["apple", "banana", "avocado", "cherry"].into_iter()),
+            2
+        );
+        assert_eq!(count_ascending(std::iter::empty::<i32>()), 0);
+    }
+}
@@ -1,10 +1,175 @@
 pub mod _2021;
 pub mod _2023;
 pub mod _2024;
-
-#[cfg(test)]
+pub mod grid;
 pub mod input_parsing;
+pub mod iter_ext;
 
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
+
+use crate::input_parsing::Input;
+
+/// Solves both parts of a puzzle, formatted as strings.
+///
+/// Every 2021 day and 2023 day 23 are dispatched. The other 2023 and 2024 solutions don't share a
+/// parse-then-solve shape yet, so they return an error here until each one gets a string entry
+/// point.
+pub fn run(year: u16, day: u8, input: Input) -> Result<(String, String), String> {
+    match year {
+        2021 => _2021::run(day, input),
+        2023 => _2023::run(day, input),
+        2024 => Err(format!("{year} isn't wired up to run yet")),
+        _ => Err(format!("{year} day {day} is not implemented")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_parsing::Input::*;
+
+    #[test]
+    fn test_run_sample() {
+        let depths = Raw("
+        199
+        200
+        208
+        210
+        200
+        207
+        240
+        269
+        260
+        263
+        ");
+
+        assert_eq!(run(2021, 1, depths), Ok(("7".to_string(), "5".to_string())));
+        assert_eq!(
+            run(2021, 6, Raw("3,4,3,1,2")),
+            Ok(("5934".to_string(), "26984457539".to_string()))
+        );
+        let island = Raw("
+        #.###
+        #...#
+        ###.#
+        ");
+        assert_eq!(
+            run(2023, 23, island),
+            Ok(("4".to_string(), "4".to_string()))
+        );
+        assert!(run(2021, 26, Raw("")).is_err());
+        assert!(run(2015, 1, Raw("")).is_err());
+        assert_eq!(
+            run(2023, 1, Raw("")),
+            Err("2023 day 1 isn't wired up to run yet".to_string())
+        );
+        assert_eq!(
+            run(2024, 1, Raw("")),
+            Err("2024 isn't wired up to run yet".to_string())
+        );
+    }
+
+    #[test]
+    fn test_run_errors() {
+        for day in 1..=8 {
+            assert_eq!(
+                run(2021, day, Raw("")),
+                Err("input is empty".to_string()),
+                "day {day}"
+            );
+        }
+        assert_eq!(run(2023, 23, Raw("")), Err("input is empty".to_string()));
+        assert_eq!(
+            run(2023, 23, Raw("#.#\n#?#")),
+            Err("unknown char ?".to_string())
+        );
+        assert_eq!(
+            run(2021, 3, Raw("101\n121")),
+            Err("invalid bit: 2".to_string())
+        );
+        assert!(run(2021, 3, Raw("101\n10")).is_err());
+        assert_eq!(
+            run(2021, 2, Raw("forward 5\nsideways 2")),
+            Err("invalid command: sideways".to_string())
+        );
+        assert_eq!(
+            run(
+                2021,
+                4,
+                Raw("
+                99,98
+
+                1  2  3  4  5
+                6  7  8  9 10
+               11 12 13 14 15
+               16 17 18 19 20
+               21 22 23 24 25
+                ")
+            ),
+            Err("no board wins".to_string())
+        );
+    }
+
+    #[test]
+    fn test_run_rejects_malformed_input() {
+        let is_bad_number =
+            |result: Result<_, String>| result.is_err_and(|e| e.starts_with("invalid number"));
+        assert!(is_bad_number(run(2021, 1, Raw("199\ndeep\n208"))));
+        assert!(is_bad_number(run(2021, 6, Raw("3,x,4"))));
+        assert!(is_bad_number(run(2021, 7, Raw("a,b"))));
+        assert!(is_bad_number(run(2021, 7, Raw("16,1,2.5"))));
+
+        assert_eq!(run(2021, 6, Raw("9")), Err("invalid timer: 9".to_string()));
+        assert_eq!(
+            run(2021, 5, Raw("0,9 -> 5,9\n0,9 5,9")),
+            Err("invalid row: 0,9 5,9".to_string())
+        );
+        assert_eq!(
+            run(2021, 8, Raw("garbage")),
+            Err("invalid row: garbage".to_string())
+        );
+        assert_eq!(
+            run(2021, 8, Raw("ab | cd")),
+            Err("invalid row: ab | cd".to_string())
+        );
+        // ten signals and four outputs, but no wiring fits them
+        assert!(run(2021, 8, Raw("a b c d e f g h i j | a b c d")).is_err());
+
+        let board = "
+            22,13,17,11,0
+
+            22 13 17 11  0
+             8  2 23  4 24
+            21  9 14 16  7
+             6 10  3 18  5
+             1 12 20 15 19";
+        assert!(run(2021, 4, Raw(board)).is_ok());
+        assert_eq!(
+            run(
+                2021,
+                4,
+                Raw(&board.replace("21  9 14 16  7", "21  9 14 16  7 30"))
+            ),
+            Err("invalid row: 21  9 14 16  7 30".to_string())
+        );
+        assert_eq!(
+            run(
+                2021,
+                4,
+                Raw(&board.replace("21  9 14 16  7", "21  9 14 16"))
+            ),
+            Err("invalid row: 21  9 14 16".to_string())
+        );
+        assert!(is_bad_number(run(
+            2021,
+            4,
+            Raw(&board.replace("22,13", "22,thirteen"))
+        )));
+        assert_eq!(
+            run(2021, 4, Raw(&board.replace("1 12 20 15 19", ""))),
+            Err("board has fewer than 5 rows".to_string())
+        );
+    }
+}